@@ -1,7 +1,9 @@
+use crate::cli::Options;
 use crate::controller::{AppEvents, Controller};
-use ratatui::DefaultTerminal;
+use crate::tui::Tui;
 use ratatui::Frame;
 use std::io;
+use std::path::PathBuf;
 
 pub struct App {
     controller: Controller,
@@ -9,26 +11,41 @@ pub struct App {
 }
 
 impl App {
-    pub fn new() -> App {
+    pub fn new(options: &Options) -> App {
         App {
             exit: false,
-            controller: Controller::new(),
+            controller: Controller::new(options),
         }
     }
 
-    pub fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+    pub fn run(&mut self, terminal: &mut Tui) -> io::Result<()> {
         terminal.clear()?;
         while !self.exit {
-            terminal.draw(|frame: &mut Frame<'_>| self.controller.draw(frame))?;
+            if self.controller.take_redraw() {
+                terminal.draw(|frame: &mut Frame<'_>| self.controller.draw(frame))?;
+            }
             match self.controller.handle_events() {
                 Err(e) => return Err(e),
                 Ok(event) => match event {
                     AppEvents::None => {}
                     AppEvents::Exit => self.exit = true,
+                    AppEvents::OpenExternalEditor => {
+                        self.controller.open_external_editor(terminal)?
+                    }
                     _ => panic!(),
                 },
             }
         }
         Ok(())
     }
+
+    /// the directory the explorer shows, written for the shell on quit
+    pub fn current_dir(&self) -> PathBuf {
+        self.controller.file_manager.current_dir()
+    }
+
+    /// the paths chosen in the picker mode, empty if it was cancelled
+    pub fn picked(&self) -> &[PathBuf] {
+        self.controller.file_manager.picked()
+    }
 }
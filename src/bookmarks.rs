@@ -0,0 +1,116 @@
+//! Vim style marks: directories saved under a letter, kept in
+//! `$XDG_CONFIG_HOME/pathexplorer/bookmarks` between sessions.
+
+use crate::util;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub struct Bookmarks {
+    file: Option<PathBuf>,
+    marks: BTreeMap<char, PathBuf>,
+}
+
+impl Bookmarks {
+    /// load the marks from the config directory, nothing is saved if there is none
+    pub fn load_default() -> (Bookmarks, Option<io::Error>) {
+        let file = util::xdg_dir("XDG_CONFIG_HOME", ".config")
+            .map(|config| config.join("pathexplorer").join("bookmarks"));
+        match file {
+            Some(file) => Bookmarks::load(file),
+            None => (
+                Bookmarks {
+                    file: None,
+                    marks: BTreeMap::new(),
+                },
+                None,
+            ),
+        }
+    }
+
+    /// load the marks from `file`, a missing file means there are no marks
+    pub fn load(file: PathBuf) -> (Bookmarks, Option<io::Error>) {
+        let content = fs::read_to_string(&file);
+        let mut bookmarks = Bookmarks {
+            file: Some(file),
+            marks: BTreeMap::new(),
+        };
+        let content = match content {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return (bookmarks, None),
+            Err(e) => return (bookmarks, Some(e)),
+        };
+        //one mark per line: the letter and the path, separated by a tab
+        for line in content.lines() {
+            let mut chars = line.chars();
+            if let (Some(letter), Some('\t')) = (chars.next(), chars.next())
+                && Bookmarks::is_valid(letter)
+            {
+                bookmarks
+                    .marks
+                    .insert(letter, PathBuf::from(chars.as_str()));
+            }
+        }
+        (bookmarks, None)
+    }
+
+    /// marks are named by ascii letters
+    pub fn is_valid(letter: char) -> bool {
+        letter.is_ascii_alphabetic()
+    }
+
+    pub fn get(&self, letter: char) -> Option<&Path> {
+        self.marks.get(&letter).map(PathBuf::as_path)
+    }
+
+    /// all marks ordered by their letter
+    pub fn marks(&self) -> impl Iterator<Item = (char, &Path)> {
+        self.marks
+            .iter()
+            .map(|(letter, path)| (*letter, path.as_path()))
+    }
+
+    /// save `path` under `letter`, an existing mark is replaced
+    pub fn set(&mut self, letter: char, path: PathBuf) -> io::Result<()> {
+        self.marks.insert(letter, path);
+        self.save()
+    }
+
+    pub fn remove(&mut self, letter: char) -> io::Result<()> {
+        self.marks.remove(&letter);
+        self.save()
+    }
+
+    /// move the mark `from` to the letter `to`, a mark at `to` takes the place of `from`
+    pub fn rename(&mut self, from: char, to: char) -> io::Result<()> {
+        let Some(path) = self.marks.remove(&from) else {
+            return Ok(());
+        };
+        if let Some(replaced) = self.marks.insert(to, path) {
+            self.marks.insert(from, replaced);
+        }
+        self.save()
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let Some(file) = &self.file else {
+            return Ok(());
+        };
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content: String = self
+            .marks
+            .iter()
+            .filter_map(|(letter, path)| {
+                let path = path.to_str()?;
+                //a newline would break the format, such paths are not saved
+                (!path.contains('\n')).then(|| format!("{letter}\t{path}\n"))
+            })
+            .collect();
+        let temp = file.with_extension("tmp");
+        fs::write(&temp, content)?;
+        fs::rename(temp, file)
+    }
+}
@@ -0,0 +1,231 @@
+//! The command line: `pathexplorer [OPTIONS] [PATH]`.
+
+use crate::file_manager::Sorting;
+use crate::shell_init;
+use crate::util;
+use std::ffi::OsString;
+use std::fs;
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage: pathexplorer [OPTIONS] [PATH]
+
+Browse PATH or the current directory. If PATH is a file, its directory is
+shown with the file highlighted.
+
+Options:
+      --show-hidden    show hidden files
+      --sort <MODE>    sort by unsorted, name_ascending, name_descending,
+                       size_ascending or size_descending
+      --config <FILE>  read the config from FILE instead of
+                       $XDG_CONFIG_HOME/pathexplorer/config.toml
+      --read-only      don't change any files
+      --choosedir <FILE>
+                       write the last directory into FILE on quit
+      --print-cwd      print the last directory on quit, the explorer is
+                       drawn on stderr then
+      --pick[=files|dirs|any]
+                       choose paths for another program instead of browsing,
+                       <Enter> picks the highlighted entry (files mode enters
+                       folders, dirs mode picks the current folder if a file
+                       is highlighted) and <Esc> cancels with status 130.
+                       Files are never changed while picking
+      --multiple       pick the selection (<y>) with <Enter>
+      --output <FILE>  write the picked paths into FILE instead of stdout,
+                       the explorer is drawn on stderr without it
+  -0, --null           end the picked paths with NUL instead of a newline
+      --shell-init <SHELL>
+                       print a `pe` function for bash, zsh or fish that
+                       changes to the last directory on quit, e.g.
+                       eval \"$(pathexplorer --shell-init bash)\"
+  -h, --help           print this help
+  -V, --version        print the version
+";
+
+pub enum Command {
+    Run(Options),
+    Help,
+    Version,
+    //print the shell function
+    ShellInit(&'static str),
+}
+
+/// what the picker mode may choose
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum PickMode {
+    Files,
+    Dirs,
+    Any,
+}
+
+pub struct Options {
+    //absolute path of the directory to start in
+    pub start_dir: PathBuf,
+    //the file given on the command line, highlighted in start_dir
+    pub start_file: Option<PathBuf>,
+    pub show_hidden: bool,
+    pub sorting: Option<Sorting>,
+    pub config: Option<PathBuf>,
+    pub read_only: bool,
+    //where the last directory is written on quit
+    pub choosedir: Option<PathBuf>,
+    pub print_cwd: bool,
+    //choose paths instead of browsing, None outside of the picker mode
+    pub pick: Option<PickMode>,
+    pub multiple: bool,
+    //where the picked paths are written, stdout without one
+    pub output: Option<PathBuf>,
+    pub null: bool,
+}
+
+/// parse the arguments without the program name. Paths are checked here, so every problem
+/// is known before the terminal is taken over
+pub fn parse(args: impl IntoIterator<Item = OsString>) -> Result<Command, String> {
+    let mut args = args.into_iter();
+    let mut path = None;
+    let mut show_hidden = false;
+    let mut sorting = None;
+    let mut config = None;
+    let mut read_only = false;
+    let mut choosedir = None;
+    let mut print_cwd = false;
+    let mut pick = None;
+    let mut multiple = false;
+    let mut output = None;
+    let mut null = false;
+    let mut options_done = false;
+    while let Some(arg) = args.next() {
+        let text = arg.to_string_lossy();
+        if options_done || !text.starts_with('-') || text == "-" {
+            if path.is_some() {
+                return Err(format!(
+                    "unexpected argument {text:?}, only one path is allowed"
+                ));
+            }
+            path = Some(PathBuf::from(arg));
+            continue;
+        }
+        //`--sort=name_ascending` is the same as `--sort name_ascending`
+        let (name, inline_value) = match text.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => (name, Some(OsString::from(value))),
+            _ => (text.as_ref(), None),
+        };
+        if inline_value.is_some()
+            && !matches!(
+                name,
+                "--sort" | "--config" | "--choosedir" | "--shell-init" | "--pick" | "--output"
+            )
+        {
+            return Err(format!("{name} doesn't take a value"));
+        }
+        let mut value = || {
+            inline_value
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("{name} needs a value"))
+        };
+        match name {
+            "--" => options_done = true,
+            "-h" | "--help" => return Ok(Command::Help),
+            "-V" | "--version" => return Ok(Command::Version),
+            "--show-hidden" => show_hidden = true,
+            "--read-only" => read_only = true,
+            "--sort" => sorting = Some(parse_sorting(&value()?.to_string_lossy())?),
+            "--config" => config = Some(PathBuf::from(value()?)),
+            "--choosedir" => choosedir = Some(PathBuf::from(value()?)),
+            "--print-cwd" => print_cwd = true,
+            //the mode is optional, so it can only be given with `=`
+            "--pick" => {
+                pick = Some(match &inline_value {
+                    None => PickMode::Any,
+                    Some(mode) => parse_pick_mode(&mode.to_string_lossy())?,
+                })
+            }
+            "--multiple" => multiple = true,
+            "--output" => output = Some(PathBuf::from(value()?)),
+            "-0" | "--null" => null = true,
+            "--shell-init" => {
+                let shell = value()?.to_string_lossy().into_owned();
+                return shell_init::script(&shell)
+                    .map(Command::ShellInit)
+                    .ok_or_else(|| {
+                        format!(
+                            "unknown shell {shell:?}, expected {}",
+                            shell_init::SHELLS.join(", ")
+                        )
+                    });
+            }
+            _ => return Err(format!("unknown option {name}")),
+        }
+    }
+
+    if pick.is_none() {
+        let picker_only = [
+            ("--multiple", multiple),
+            ("--output", output.is_some()),
+            ("--null", null),
+        ];
+        if let Some((name, _)) = picker_only.iter().find(|(_, given)| *given) {
+            return Err(format!("{name} only works with --pick"));
+        }
+    } else if print_cwd && output.is_none() {
+        return Err("--print-cwd and the picked paths can't both go to stdout".to_owned());
+    }
+
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("/"));
+    let (start_dir, start_file) = match path {
+        None => (cwd, None),
+        Some(path) => {
+            let absolute = util::normalize_path(&cwd.join(&path));
+            let metadata = fs::metadata(&absolute)
+                .map_err(|e| format!("cannot open {}: {e}", path.display()))?;
+            match absolute.parent() {
+                Some(parent) if !metadata.is_dir() => (parent.to_path_buf(), Some(absolute)),
+                _ => (absolute, None),
+            }
+        }
+    };
+    //a missing config file only means the defaults, unless it was asked for
+    if let Some(config) = &config
+        && let Err(e) = fs::metadata(config)
+    {
+        return Err(format!("cannot read config {}: {e}", config.display()));
+    }
+    Ok(Command::Run(Options {
+        start_dir,
+        start_file,
+        show_hidden,
+        sorting,
+        config,
+        read_only: read_only || pick.is_some(),
+        choosedir,
+        print_cwd,
+        pick,
+        multiple,
+        output,
+        null,
+    }))
+}
+
+/// the sort modes are named like in the config file
+fn parse_sorting(name: &str) -> Result<Sorting, String> {
+    toml::Value::String(name.to_owned())
+        .try_into()
+        .map_err(|_| {
+            format!(
+                "unknown sort mode {name:?}, expected unsorted, name_ascending, \
+                 name_descending, size_ascending or size_descending"
+            )
+        })
+}
+
+fn parse_pick_mode(mode: &str) -> Result<PickMode, String> {
+    match mode {
+        "files" => Ok(PickMode::Files),
+        "dirs" => Ok(PickMode::Dirs),
+        "any" => Ok(PickMode::Any),
+        _ => Err(format!(
+            "unknown pick mode {mode:?}, expected files, dirs or any"
+        )),
+    }
+}
@@ -0,0 +1,369 @@
+//! The columns of the explorer table and how their cells are computed.
+
+use crate::file_manager::FileManager;
+use crate::util;
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::fs::{DirEntry, Metadata};
+use std::os::unix::fs::{FileTypeExt, MetadataExt, PermissionsExt};
+use unicode_width::UnicodeWidthChar;
+use unicode_width::UnicodeWidthStr;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Column {
+    Name,
+    Size,
+    Modified,
+    Changed,
+    Accessed,
+    Permissions,
+    Owner,
+    Group,
+    Type,
+    LinkTarget,
+}
+
+impl Column {
+    /// all columns in the order they are offered in the columns popup
+    pub const ALL: [Column; 10] = [
+        Column::Name,
+        Column::Size,
+        Column::Modified,
+        Column::Changed,
+        Column::Accessed,
+        Column::Permissions,
+        Column::Owner,
+        Column::Group,
+        Column::Type,
+        Column::LinkTarget,
+    ];
+
+    pub fn header(&self) -> &'static str {
+        match self {
+            Column::Name => "FILENAME",
+            Column::Size => "SIZE",
+            Column::Modified => "MODIFIED",
+            Column::Changed => "CHANGED",
+            Column::Accessed => "ACCESSED",
+            Column::Permissions => "PERMISSIONS",
+            Column::Owner => "OWNER",
+            Column::Group => "GROUP",
+            Column::Type => "TYPE",
+            Column::LinkTarget => "LINK TARGET",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Column::Name => "name",
+            Column::Size => "size",
+            Column::Modified => "modification time",
+            Column::Changed => "status change time",
+            Column::Accessed => "access time",
+            Column::Permissions => "permissions",
+            Column::Owner => "owner",
+            Column::Group => "group",
+            Column::Type => "type / extension",
+            Column::LinkTarget => "symlink target",
+        }
+    }
+
+    /// the content of the cell of `entry` in this column
+    pub fn value(
+        &self,
+        entry: &DirEntry,
+        meta: Option<&Metadata>,
+        file_manager: &FileManager,
+        names: &mut NameCache,
+    ) -> String {
+        let Some(meta) = meta else {
+            return match self {
+                Column::Name => entry.file_name().to_string_lossy().into_owned(),
+                _ => String::new(),
+            };
+        };
+        match self {
+            Column::Name => entry.file_name().to_string_lossy().into_owned(),
+            Column::Size if meta.is_dir() => {
+                let path = entry.path();
+                match file_manager.dir_size(&path) {
+                    Some(size) => util::format_bytes(size, file_manager.si_units),
+                    None if file_manager.dir_size_pending(&path) => String::from("…"),
+                    None => String::new(),
+                }
+            }
+            Column::Size => util::format_bytes(meta.len(), file_manager.si_units),
+            Column::Modified => format_time(meta.mtime()),
+            Column::Changed => format_time(meta.ctime()),
+            Column::Accessed => format_time(meta.atime()),
+            Column::Permissions => permission_string(meta),
+            Column::Owner => names.user(meta.uid()),
+            Column::Group => names.group(meta.gid()),
+            Column::Type => file_type(entry, meta),
+            Column::LinkTarget => std::fs::read_link(entry.path())
+                .map(|target| target.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// the widest a column gets, the name column takes whatever is left
+    fn max_width(&self) -> usize {
+        match self {
+            Column::Name => usize::MAX,
+            Column::Owner | Column::Group => 16,
+            Column::Type => 10,
+            Column::LinkTarget => 40,
+            _ => 20,
+        }
+    }
+}
+
+/// Fit the columns into `available` cells: every column gets the width of its widest cell (up
+/// to a limit), the name column gets the rest. Columns at the end are dropped while the name
+/// column would be narrower than `min_name_width`. Returns the kept columns with their widths.
+pub fn layout_widths(
+    columns: &[(Column, usize)],
+    available: usize,
+    min_name_width: usize,
+) -> Vec<(Column, usize)> {
+    let mut kept: Vec<(Column, usize)> = columns
+        .iter()
+        .map(|(column, width)| (*column, (*width).min(column.max_width())))
+        .collect();
+    loop {
+        let spacing = kept.len().saturating_sub(1);
+        let others: usize = kept
+            .iter()
+            .filter(|(column, _)| *column != Column::Name)
+            .map(|(_, width)| width)
+            .sum();
+        let remaining = available.saturating_sub(others + spacing);
+        let name = kept.iter().position(|(column, _)| *column == Column::Name);
+        let last_other = kept.iter().rposition(|(column, _)| *column != Column::Name);
+
+        match (name, last_other) {
+            (Some(_), Some(last)) if remaining < min_name_width && kept.len() > 1 => {
+                kept.remove(last);
+            }
+            (Some(name), _) => {
+                kept[name].1 = remaining;
+                return kept;
+            }
+            (None, _) => return kept,
+        }
+    }
+}
+
+/// the indentation guides in front of the names of a tree, given the depth of every entry.
+/// Entries with a sibling below them get "├─ ", the last ones "└─ ". The guides leave room
+/// for a two cell wide expansion marker in front of every name
+pub fn tree_guides(depths: &[usize]) -> Vec<String> {
+    let mut guides = vec![String::new(); depths.len()];
+    //whether an entry of each level follows before the tree returns to a lower level
+    let mut continues: Vec<bool> = Vec::new();
+    for (index, &depth) in depths.iter().enumerate().rev() {
+        continues.resize(depth + 1, false);
+        if depth > 0 {
+            let guide = &mut guides[index];
+            guide.push_str("  ");
+            for &level_continues in &continues[1..depth] {
+                guide.push_str(if level_continues { "│    " } else { "     " });
+            }
+            guide.push_str(if continues[depth] {
+                "├─ "
+            } else {
+                "└─ "
+            });
+        }
+        continues[depth] = true;
+    }
+    guides
+}
+
+/// shorten `text` to `width` cells, the middle is replaced by "…" so the extension stays visible
+pub fn elide(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_owned();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let keep = width - 1;
+    let tail_width = keep / 3;
+    let head_width = keep - tail_width;
+
+    let mut head = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w > head_width {
+            break;
+        }
+        used += w;
+        head.push(c);
+    }
+    let mut tail: Vec<char> = Vec::new();
+    let mut used = 0;
+    for c in text.chars().rev() {
+        let w = c.width().unwrap_or(0);
+        if used + w > tail_width {
+            break;
+        }
+        used += w;
+        tail.push(c);
+    }
+    head.push('…');
+    head.extend(tail.into_iter().rev());
+    head
+}
+
+fn format_time(secs: i64) -> String {
+    let time = util::local_time(secs);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        time.year, time.month, time.day, time.hour, time.minute
+    )
+}
+
+/// permissions like `ls -l`, e.g. drwxr-xr-x
+pub fn permission_string(meta: &Metadata) -> String {
+    let mode = meta.permissions().mode();
+    let file_type = meta.file_type();
+    let kind = if file_type.is_dir() {
+        'd'
+    } else if file_type.is_symlink() {
+        'l'
+    } else if file_type.is_fifo() {
+        'p'
+    } else if file_type.is_socket() {
+        's'
+    } else if file_type.is_char_device() {
+        'c'
+    } else if file_type.is_block_device() {
+        'b'
+    } else {
+        '-'
+    };
+
+    let mut permissions = String::with_capacity(10);
+    permissions.push(kind);
+    //(read, write, execute bit, special bit, special char if executable, if not)
+    let triples = [
+        (0o400, 0o200, 0o100, 0o4000, 's', 'S'),
+        (0o040, 0o020, 0o010, 0o2000, 's', 'S'),
+        (0o004, 0o002, 0o001, 0o1000, 't', 'T'),
+    ];
+    for (read, write, execute, special, special_exec, special_no_exec) in triples {
+        permissions.push(if mode & read != 0 { 'r' } else { '-' });
+        permissions.push(if mode & write != 0 { 'w' } else { '-' });
+        permissions.push(match (mode & execute != 0, mode & special != 0) {
+            (true, true) => special_exec,
+            (false, true) => special_no_exec,
+            (true, false) => 'x',
+            (false, false) => '-',
+        });
+    }
+    permissions
+}
+
+/// the extension of files, the kind of everything else
+fn file_type(entry: &DirEntry, meta: &Metadata) -> String {
+    let file_type = meta.file_type();
+    let kind = if file_type.is_dir() {
+        "dir"
+    } else if file_type.is_symlink() {
+        "link"
+    } else if file_type.is_fifo() {
+        "fifo"
+    } else if file_type.is_socket() {
+        "socket"
+    } else if file_type.is_char_device() {
+        "char dev"
+    } else if file_type.is_block_device() {
+        "block dev"
+    } else {
+        return entry
+            .path()
+            .extension()
+            .map(|extension| extension.to_string_lossy().into_owned())
+            .unwrap_or_else(|| String::from("file"));
+    };
+    kind.to_owned()
+}
+
+/// Caches the names of users and groups, looking them up for every row would be too slow
+pub struct NameCache {
+    users: HashMap<u32, String>,
+    groups: HashMap<u32, String>,
+}
+
+impl NameCache {
+    pub fn new() -> NameCache {
+        NameCache {
+            users: HashMap::new(),
+            groups: HashMap::new(),
+        }
+    }
+
+    /// name of the user, the uid if it has no name
+    pub fn user(&mut self, uid: u32) -> String {
+        self.users
+            .entry(uid)
+            .or_insert_with(|| user_name(uid).unwrap_or_else(|| uid.to_string()))
+            .clone()
+    }
+
+    /// name of the group, the gid if it has no name
+    pub fn group(&mut self, gid: u32) -> String {
+        self.groups
+            .entry(gid)
+            .or_insert_with(|| group_name(gid).unwrap_or_else(|| gid.to_string()))
+            .clone()
+    }
+}
+
+fn user_name(uid: u32) -> Option<String> {
+    let mut buffer = vec![0 as libc::c_char; 4096];
+    // SAFETY: passwd is a plain C struct for which all zero bytes are a valid value
+    let mut passwd: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut result: *mut libc::passwd = std::ptr::null_mut();
+    // SAFETY: all pointers are valid for the duration of the call, the buffer length is correct
+    let error = unsafe {
+        libc::getpwuid_r(
+            uid,
+            &mut passwd,
+            buffer.as_mut_ptr(),
+            buffer.len(),
+            &mut result,
+        )
+    };
+    if error != 0 || result.is_null() {
+        return None;
+    }
+    // SAFETY: on success pw_name points to a nul terminated string inside buffer
+    let name = unsafe { CStr::from_ptr(passwd.pw_name) };
+    Some(name.to_string_lossy().into_owned())
+}
+
+fn group_name(gid: u32) -> Option<String> {
+    let mut buffer = vec![0 as libc::c_char; 4096];
+    // SAFETY: group is a plain C struct for which all zero bytes are a valid value
+    let mut group: libc::group = unsafe { std::mem::zeroed() };
+    let mut result: *mut libc::group = std::ptr::null_mut();
+    // SAFETY: all pointers are valid for the duration of the call, the buffer length is correct
+    let error = unsafe {
+        libc::getgrgid_r(
+            gid,
+            &mut group,
+            buffer.as_mut_ptr(),
+            buffer.len(),
+            &mut result,
+        )
+    };
+    if error != 0 || result.is_null() {
+        return None;
+    }
+    // SAFETY: on success gr_name points to a nul terminated string inside buffer
+    let name = unsafe { CStr::from_ptr(group.gr_name) };
+    Some(name.to_string_lossy().into_owned())
+}
@@ -0,0 +1,171 @@
+//! Preferences read from `$XDG_CONFIG_HOME/pathexplorer/config.toml`, e.g.
+//!
+//! ```toml
+//! show_hidden = true
+//! sorting = "name_ascending"
+//! dir_sorting = "start"
+//! save_preferences = true
+//! ```
+//!
+//! Unknown keys are errors so typos don't go unnoticed. With `save_preferences` the sorting and
+//! hidden files chosen during a session are written back, the rest of the file is kept as is.
+
+use crate::file_manager::{SortDir, Sorting};
+use crate::util;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct Config {
+    pub show_hidden: bool,
+    pub sorting: Sorting,
+    pub dir_sorting: SortDir,
+    //whether the fuzzy finder skips entries ignored by .gitignore files
+    pub respect_gitignore: bool,
+    //show sizes in powers of 1000 (kB) instead of 1024 (KiB)
+    pub si_units: bool,
+    //the number of errors kept in the error log
+    pub error_log_capacity: usize,
+    //width of the listing in percent, the error log takes the rest
+    pub table_width: u16,
+    //width of the listing in percent while the preview is shown
+    pub preview_table_width: u16,
+    //write the sorting and hidden files back when they are changed
+    pub save_preferences: bool,
+    #[serde(skip)]
+    file: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            show_hidden: false,
+            sorting: Sorting::Unsorted,
+            dir_sorting: SortDir::Unsorted,
+            respect_gitignore: true,
+            si_units: false,
+            error_log_capacity: 20,
+            table_width: 70,
+            preview_table_width: 50,
+            save_preferences: false,
+            file: None,
+        }
+    }
+}
+
+impl Config {
+    /// load the config from the config directory, the defaults are used without one
+    pub fn load_default() -> (Config, Vec<io::Error>) {
+        match util::xdg_dir("XDG_CONFIG_HOME", ".config") {
+            Some(config) => Config::load(config.join("pathexplorer").join("config.toml")),
+            None => (Config::default(), Vec::new()),
+        }
+    }
+
+    /// load the config from `file`, a missing file means the defaults. An invalid file is
+    /// reported and the defaults are used instead
+    pub fn load(file: PathBuf) -> (Config, Vec<io::Error>) {
+        let content = match fs::read_to_string(&file) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return (Config::with_file(file), Vec::new());
+            }
+            Err(e) => return (Config::with_file(file), vec![e]),
+        };
+        let mut config = match toml::from_str::<Config>(&content) {
+            Ok(config) => config,
+            Err(e) => {
+                let line = e
+                    .span()
+                    .map_or(1, |span| content[..span.start].matches('\n').count() + 1);
+                let error = invalid(format!("{} line {line}: {}", file.display(), e.message()));
+                //don't overwrite a file that couldn't be read
+                return (Config::default(), vec![error]);
+            }
+        };
+        config.file = Some(file);
+        let errors = config.validate();
+        (config, errors)
+    }
+
+    fn with_file(file: PathBuf) -> Config {
+        Config {
+            file: Some(file),
+            ..Config::default()
+        }
+    }
+
+    /// replace values that would break the layout with their defaults
+    fn validate(&mut self) -> Vec<io::Error> {
+        let default = Config::default();
+        let mut errors = Vec::new();
+        if self.error_log_capacity == 0 {
+            errors.push("error_log_capacity must be at least 1".to_owned());
+            self.error_log_capacity = default.error_log_capacity;
+        }
+        if !(20..=90).contains(&self.table_width) {
+            errors.push("table_width must be between 20 and 90".to_owned());
+            self.table_width = default.table_width;
+        }
+        if !(20..=90).contains(&self.preview_table_width) {
+            errors.push("preview_table_width must be between 20 and 90".to_owned());
+            self.preview_table_width = default.preview_table_width;
+        }
+        let file = self.file.as_ref().map(|file| file.display().to_string());
+        errors
+            .into_iter()
+            .map(|e| invalid(format!("{}: {e}", file.as_deref().unwrap_or_default())))
+            .collect()
+    }
+
+    /// write the preferences back if `save_preferences` is set, comments and other keys of
+    /// the file are kept
+    pub fn save_preferences(
+        &mut self,
+        show_hidden: bool,
+        sorting: Sorting,
+        dir_sorting: SortDir,
+    ) -> io::Result<()> {
+        self.show_hidden = show_hidden;
+        self.sorting = sorting;
+        self.dir_sorting = dir_sorting;
+        let Some(file) = &self.file else {
+            return Ok(());
+        };
+        if !self.save_preferences {
+            return Ok(());
+        }
+        let content = match fs::read_to_string(file) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+        let mut document: toml_edit::DocumentMut = content
+            .parse()
+            .map_err(|e: toml_edit::TomlError| invalid(e.message().to_owned()))?;
+        document["show_hidden"] = toml_edit::value(show_hidden);
+        document["sorting"] = toml_edit::value(name(sorting)?);
+        document["dir_sorting"] = toml_edit::value(name(dir_sorting)?);
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let temp = file.with_extension("tmp");
+        fs::write(&temp, document.to_string())?;
+        fs::rename(temp, file)
+    }
+}
+
+/// the name of a unit variant in the config file
+fn name(value: impl Serialize) -> io::Result<String> {
+    match toml::Value::try_from(value) {
+        Ok(toml::Value::String(name)) => Ok(name),
+        _ => Err(invalid("value has no name".to_owned())),
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
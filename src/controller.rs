@@ -1,36 +1,69 @@
+use crate::cli::Options;
 use crate::file_manager::FileManager;
 use crate::message::{Message, MessageReceiver, MessageSender};
+use crate::tui::{self, Tui};
+use crate::util;
+use crate::windows::disk_usage_table::DiskUsageTable;
 use crate::windows::explorer_table::ExplorerTable;
+use crate::windows::popups::bookmarks_popup::BookmarksPopup;
+use crate::windows::popups::columns_popup::ColumnsPopup;
 use crate::windows::popups::confirmation_popup::ConfirmationPopup;
+use crate::windows::popups::conflict_popup::ConflictPopup;
+use crate::windows::popups::fuzzy_finder_popup::FuzzyFinderPopup;
+use crate::windows::popups::history_popup::HistoryPopup;
 use crate::windows::popups::key_mapping_popup::KeyMappingPopup;
 use crate::windows::popups::new_file_popup::NewFilePopup;
+use crate::windows::popups::recent_dirs_popup::RecentDirsPopup;
 use crate::windows::popups::sorting_popup::SortingPopUp;
 use crate::windows::popups::text_field_popup::TextFieldPopup;
+use crate::windows::trash_table::TrashTable;
 use crossterm::event;
 use crossterm::event::{Event, KeyEvent, KeyEventKind};
 use ratatui::Frame;
 use std::io;
+use std::time::Duration;
+
+//how long to wait for input before the jobs are polled, the ui is only redrawn after input or
+//while something changes in the background
+const TICK_RATE: Duration = Duration::from_millis(100);
 
 pub enum AppEvents {
     None,
     Exit,
     OpenSortingPopupWindow,
+    OpenColumnsPopup,
     ChangeToExplorerWindow,
+    ChangeToTrashWindow,
+    ChangeToDiskUsageWindow,
     OpenKeyMappingPopupWindow,
     OpenTextFieldPopup,
     OpenNewFilePopup,
     OpenConfirmationPopup,
+    OpenHistoryPopup,
+    OpenConflictPopup,
+    OpenFuzzyFinderPopup,
+    OpenRecentDirsPopup,
+    OpenBookmarksPopup,
+    OpenExternalEditor,
     ClosePopUp,
 }
 
 #[derive(Eq, Hash, PartialEq, Clone, Copy)]
 pub enum AppWindows {
     Explorer = 0,
+    Trash = 1,
+    DiskUsage = 2,
 }
 
 pub trait State: MessageReceiver + MessageSender {
     fn enter(&mut self, _file_manager: &mut FileManager) {}
     fn exit(&mut self, _file_manager: &mut FileManager) {}
+    ///files in the current directory were changed by another process
+    fn files_changed(&mut self, _file_manager: &mut FileManager) {}
+    ///true while the window waits for a result of a worker thread and has to be redrawn
+    fn is_busy(&self, _file_manager: &FileManager) -> bool {
+        false
+    }
     fn handle_key_event(
         &mut self,
         key_event: KeyEvent,
@@ -40,19 +73,34 @@ pub trait State: MessageReceiver + MessageSender {
 }
 
 pub struct Controller {
-    pub all_windows: [Box<dyn State>; 1],
+    pub all_windows: [Box<dyn State>; 3],
     pub current_window_index: AppWindows,
     pub popup_stack: Vec<Box<dyn State>>,
     pub file_manager: FileManager,
+    //the top popup asks whether the unfinished jobs may be cancelled to quit
+    quit_prompt: bool,
+    //something changed since the last draw
+    redraw: bool,
 }
 
 impl Controller {
-    pub fn new() -> Controller {
+    pub fn new(options: &Options) -> Controller {
+        let file_manager = FileManager::new(options);
+        let mut explorer_table = ExplorerTable::new(file_manager.config());
+        if let Some(file) = &options.start_file {
+            explorer_table.select_path(file, &file_manager);
+        }
         Controller {
-            all_windows: [Box::new(ExplorerTable::new())],
+            all_windows: [
+                Box::new(explorer_table),
+                Box::new(TrashTable::new()),
+                Box::new(DiskUsageTable::new()),
+            ],
             current_window_index: AppWindows::Explorer,
             popup_stack: Vec::new(),
-            file_manager: FileManager::new(),
+            file_manager,
+            quit_prompt: false,
+            redraw: true,
         }
     }
 
@@ -62,7 +110,36 @@ impl Controller {
         self.all_windows[self.current_window_index as usize].enter(&mut self.file_manager);
     }
 
+    ///returns true once if the ui has to be drawn again
+    pub fn take_redraw(&mut self) -> bool {
+        std::mem::take(&mut self.redraw)
+    }
+
+    ///true while jobs, dir sizes or the windows wait for results in the background
+    fn is_busy(&self) -> bool {
+        self.file_manager.is_busy()
+            || self.all_windows[self.current_window_index as usize].is_busy(&self.file_manager)
+            || self
+                .popup_stack
+                .iter()
+                .any(|popup| popup.is_busy(&self.file_manager))
+    }
+
     pub fn handle_events(&mut self) -> io::Result<AppEvents> {
+        let busy = self.is_busy();
+        let jobs_finished = self.file_manager.poll_jobs();
+        let sizes_changed = self.file_manager.poll_dir_sizes();
+        let files_changed = self.file_manager.poll_watcher();
+        if files_changed {
+            for window in &mut self.all_windows {
+                window.files_changed(&mut self.file_manager);
+            }
+        }
+        self.redraw |= busy || jobs_finished || sizes_changed || files_changed;
+        if !event::poll(TICK_RATE)? {
+            return Ok(AppEvents::None);
+        }
+        self.redraw = true;
         match event::read()? {
             // it's important to check that the event is a key press event as
             // crossterm also emits key release and repeat events on Windows.
@@ -80,18 +157,48 @@ impl Controller {
 
                 match app_event {
                     AppEvents::None => Ok(AppEvents::None),
-                    AppEvents::Exit => Ok(AppEvents::Exit),
+                    AppEvents::Exit => {
+                        let unfinished = self.file_manager.unfinished_jobs();
+                        if unfinished > 0 {
+                            let message = Message::String(format!(
+                                "{unfinished} job(s) are not finished yet, cancel them and quit?"
+                            ));
+                            self.popup_stack.push(Box::new(ConfirmationPopup::new(
+                                Some(message),
+                                &mut self.file_manager,
+                            )));
+                            self.quit_prompt = true;
+                            return Ok(AppEvents::None);
+                        }
+                        Ok(AppEvents::Exit)
+                    }
                     AppEvents::OpenSortingPopupWindow => {
                         self.popup_stack
                             .push(Box::new(SortingPopUp::new(None, &mut self.file_manager)));
                         Ok(AppEvents::None)
                     }
+                    AppEvents::OpenColumnsPopup => {
+                        self.popup_stack.push(Box::new(ColumnsPopup::new()));
+                        Ok(AppEvents::None)
+                    }
                     AppEvents::ChangeToExplorerWindow => {
                         self.change_window(AppWindows::Explorer);
                         Ok(AppEvents::None)
                     }
+                    AppEvents::ChangeToTrashWindow => {
+                        self.change_window(AppWindows::Trash);
+                        Ok(AppEvents::None)
+                    }
+                    AppEvents::ChangeToDiskUsageWindow => {
+                        self.change_window(AppWindows::DiskUsage);
+                        Ok(AppEvents::None)
+                    }
                     AppEvents::OpenKeyMappingPopupWindow => {
-                        self.popup_stack.push(Box::new(KeyMappingPopup::new()));
+                        let message = self.get_current_message();
+                        self.popup_stack.push(Box::new(KeyMappingPopup::new(
+                            message,
+                            &mut self.file_manager,
+                        )));
                         Ok(AppEvents::None)
                     }
                     AppEvents::OpenTextFieldPopup => {
@@ -111,6 +218,36 @@ impl Controller {
                         Ok(AppEvents::None)
                     }
 
+                    AppEvents::OpenConflictPopup => {
+                        let message = self.get_current_message();
+                        self.popup_stack.push(Box::new(ConflictPopup::new(
+                            message,
+                            &mut self.file_manager,
+                        )));
+                        Ok(AppEvents::None)
+                    }
+                    AppEvents::OpenFuzzyFinderPopup => {
+                        self.popup_stack
+                            .push(Box::new(FuzzyFinderPopup::new(&mut self.file_manager)));
+                        Ok(AppEvents::None)
+                    }
+                    AppEvents::OpenRecentDirsPopup => {
+                        self.popup_stack
+                            .push(Box::new(RecentDirsPopup::new(&mut self.file_manager)));
+                        Ok(AppEvents::None)
+                    }
+                    AppEvents::OpenBookmarksPopup => {
+                        self.popup_stack.push(Box::new(BookmarksPopup::new()));
+                        Ok(AppEvents::None)
+                    }
+                    AppEvents::OpenHistoryPopup => {
+                        self.popup_stack.push(Box::new(HistoryPopup::new()));
+                        Ok(AppEvents::None)
+                    }
+
+                    //the terminal is owned by the app, it calls open_external_editor
+                    AppEvents::OpenExternalEditor => Ok(AppEvents::OpenExternalEditor),
+
                     AppEvents::OpenNewFilePopup => {
                         self.popup_stack
                             .push(Box::new(NewFilePopup::new(None, &mut self.file_manager)));
@@ -122,6 +259,15 @@ impl Controller {
                         //pass down message
                         let message = self.get_current_message();
                         self.popup_stack.pop();
+                        if self.quit_prompt {
+                            self.quit_prompt = false;
+                            if let Some(Message::Bool(true)) = message {
+                                //stop the workers, cancelled copies clean up after themselves
+                                self.file_manager.cancel_all_jobs();
+                                return Ok(AppEvents::Exit);
+                            }
+                            return Ok(AppEvents::None);
+                        }
                         self.send_current_message(message);
                         Ok(AppEvents::None)
                     }
@@ -140,6 +286,36 @@ impl Controller {
         }
     }
 
+    /// Suspend the terminal and let the user edit the lines of the current message in the
+    /// external editor. The edited lines are sent back to the active window, if it answers
+    /// with a message a confirmation popup is opened with it.
+    pub fn open_external_editor(&mut self, terminal: &mut Tui) -> io::Result<()> {
+        let lines = match self.get_current_message() {
+            Some(Message::StringList(lines)) => lines,
+            _ => return Ok(()),
+        };
+
+        tui::restore()?;
+        let edited = util::edit_lines_in_editor(&lines);
+        tui::resume(terminal)?;
+
+        match edited {
+            Ok(edited) => self.send_current_message(Some(Message::StringList(edited))),
+            Err(e) => {
+                self.file_manager.push_error(e);
+                self.send_current_message(None);
+            }
+        }
+
+        if let Some(message) = self.get_current_message() {
+            self.popup_stack.push(Box::new(ConfirmationPopup::new(
+                Some(message),
+                &mut self.file_manager,
+            )));
+        }
+        Ok(())
+    }
+
     /// Get message from currently active window
     pub fn get_current_message(&mut self) -> Option<Message> {
         if !self.popup_stack.is_empty() {
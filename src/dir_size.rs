@@ -0,0 +1,153 @@
+//! Recursive directory sizes, calculated on a worker thread and cached per path.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+
+/// the directories that still have to be calculated, the worker takes them from the front
+struct Queue {
+    paths: Mutex<VecDeque<PathBuf>>,
+    available: Condvar,
+    //incremented whenever the queue is replaced, a running calculation of an outdated request
+    //stops early
+    generation: AtomicU64,
+}
+
+pub struct DirSizes {
+    queue: Arc<Queue>,
+    results: Receiver<(PathBuf, u64)>,
+    sizes: HashMap<PathBuf, u64>,
+    pending: HashSet<PathBuf>,
+}
+
+impl DirSizes {
+    pub fn new() -> DirSizes {
+        let queue = Arc::new(Queue {
+            paths: Mutex::new(VecDeque::new()),
+            available: Condvar::new(),
+            generation: AtomicU64::new(0),
+        });
+        let (sender, receiver) = mpsc::channel();
+        let worker_queue = queue.clone();
+        thread::spawn(move || worker(worker_queue, sender));
+
+        DirSizes {
+            queue,
+            results: receiver,
+            sizes: HashMap::new(),
+            pending: HashSet::new(),
+        }
+    }
+
+    /// calculate the sizes of `paths` that are not cached yet, replaces all earlier requests
+    pub fn request(&mut self, paths: Vec<PathBuf>) {
+        let paths: Vec<PathBuf> = paths
+            .into_iter()
+            .filter(|path| !self.sizes.contains_key(path))
+            .collect();
+        self.pending = paths.iter().cloned().collect();
+
+        let mut queue = self.queue.paths.lock().unwrap_or_else(|e| e.into_inner());
+        self.queue.generation.fetch_add(1, Ordering::Relaxed);
+        *queue = paths.into();
+        self.queue.available.notify_one();
+    }
+
+    /// take the finished calculations, returns true if there were any
+    pub fn poll(&mut self) -> bool {
+        let mut changed = false;
+        while let Ok((path, size)) = self.results.try_recv() {
+            self.pending.remove(&path);
+            self.sizes.insert(path, size);
+            changed = true;
+        }
+        changed
+    }
+
+    /// the cached size of the directory at the absolute `path`
+    pub fn get(&self, path: &Path) -> Option<u64> {
+        self.sizes.get(path).copied()
+    }
+
+    pub fn is_pending(&self, path: &Path) -> bool {
+        self.pending.contains(path)
+    }
+
+    /// true while requested sizes are still calculated
+    pub fn is_busy(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// forget the cached sizes of `paths`, of the directories containing them and of everything
+    /// below them, e.g. after files were changed
+    pub fn invalidate(&mut self, paths: &[PathBuf]) {
+        self.poll();
+        self.request(Vec::new());
+        self.sizes.retain(|dir, _| {
+            !paths
+                .iter()
+                .any(|path| path.starts_with(dir) || dir.starts_with(path))
+        });
+    }
+
+    /// forget all cached sizes
+    pub fn clear(&mut self) {
+        self.request(Vec::new());
+        self.sizes.clear();
+    }
+}
+
+fn worker(queue: Arc<Queue>, sender: Sender<(PathBuf, u64)>) {
+    loop {
+        let (path, generation) = {
+            let mut paths = queue.paths.lock().unwrap_or_else(|e| e.into_inner());
+            loop {
+                if let Some(path) = paths.pop_front() {
+                    break (path, queue.generation.load(Ordering::Relaxed));
+                }
+                paths = queue
+                    .available
+                    .wait(paths)
+                    .unwrap_or_else(|e| e.into_inner());
+            }
+        };
+        let is_current = || queue.generation.load(Ordering::Relaxed) == generation;
+        if let Some(size) = directory_size(&path, is_current)
+            && sender.send((path, size)).is_err()
+        {
+            return;
+        }
+    }
+}
+
+/// the size of all files below `path`, symlinks are not followed and hard links are counted
+/// once. Returns None if `keep_going` turned false in the meantime.
+pub fn directory_size(path: &Path, keep_going: impl Fn() -> bool) -> Option<u64> {
+    let mut size = 0;
+    let mut seen_inodes = HashSet::new();
+    let mut stack = vec![path.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        if !keep_going() {
+            return None;
+        }
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            if meta.is_dir() {
+                stack.push(entry.path());
+            } else if meta.nlink() <= 1 || seen_inodes.insert((meta.dev(), meta.ino())) {
+                size += meta.len();
+            }
+        }
+    }
+    Some(size)
+}
@@ -0,0 +1,158 @@
+//! Disk usage of a whole directory tree, scanned on a worker thread like `ncdu` does.
+
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+/// A file or directory with the cumulative size of everything below it
+pub struct UsageNode {
+    pub name: OsString,
+    pub size: u64,
+    pub is_dir: bool,
+    /// sorted by size, the largest first
+    pub children: Vec<UsageNode>,
+}
+
+impl UsageNode {
+    /// the node reached by following the child indices in `position`
+    pub fn descendant(&self, position: &[usize]) -> Option<&UsageNode> {
+        position
+            .iter()
+            .try_fold(self, |node, index| node.children.get(*index))
+    }
+
+    /// remove the node at `position`, the sizes of all nodes above it shrink accordingly
+    pub fn remove(&mut self, position: &[usize]) -> Option<UsageNode> {
+        let (last, parents) = position.split_last()?;
+        let mut parent = &mut *self;
+        for index in parents {
+            parent = parent.children.get_mut(*index)?;
+        }
+        if *last >= parent.children.len() {
+            return None;
+        }
+        let removed = parent.children.remove(*last);
+
+        let mut node = self;
+        node.size -= removed.size;
+        for index in parents {
+            node = &mut node.children[*index];
+            node.size -= removed.size;
+        }
+        Some(removed)
+    }
+}
+
+#[derive(Default)]
+struct Progress {
+    files: AtomicU64,
+    bytes: AtomicU64,
+    cancelled: AtomicBool,
+}
+
+/// A running scan, dropping it stops the worker
+pub struct Scan {
+    root: PathBuf,
+    progress: Arc<Progress>,
+    result: Receiver<UsageNode>,
+}
+
+impl Scan {
+    pub fn start(root: PathBuf) -> Scan {
+        let progress = Arc::new(Progress::default());
+        let (sender, receiver) = mpsc::channel();
+        let worker_root = root.clone();
+        let worker_progress = progress.clone();
+        thread::spawn(move || {
+            if let Some(tree) = scan(&worker_root, &worker_progress) {
+                let _ = sender.send(tree);
+            }
+        });
+        Scan {
+            root,
+            progress,
+            result: receiver,
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// the number of files and bytes found so far
+    pub fn progress(&self) -> (u64, u64) {
+        (
+            self.progress.files.load(Ordering::Relaxed),
+            self.progress.bytes.load(Ordering::Relaxed),
+        )
+    }
+
+    /// the finished tree, None while the scan is still running
+    pub fn poll(&self) -> Option<UsageNode> {
+        self.result.try_recv().ok()
+    }
+}
+
+impl Drop for Scan {
+    fn drop(&mut self) {
+        self.progress.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+/// scan everything below `root` without leaving its file system. Symlinks are not followed and
+/// hard links are counted once. Returns None if the scan was cancelled.
+fn scan(root: &Path, progress: &Progress) -> Option<UsageNode> {
+    let meta = root.symlink_metadata().ok()?;
+    let mut seen_inodes = HashSet::new();
+    let mut node = scan_node(root, &meta, meta.dev(), &mut seen_inodes, progress)?;
+    node.name = root.as_os_str().to_owned();
+    Some(node)
+}
+
+fn scan_node(
+    path: &Path,
+    meta: &fs::Metadata,
+    device: u64,
+    seen_inodes: &mut HashSet<(u64, u64)>,
+    progress: &Progress,
+) -> Option<UsageNode> {
+    if progress.cancelled.load(Ordering::Relaxed) {
+        return None;
+    }
+    let mut node = UsageNode {
+        name: path.file_name().unwrap_or_default().to_owned(),
+        size: 0,
+        is_dir: meta.is_dir(),
+        children: Vec::new(),
+    };
+    if !meta.is_dir() {
+        if meta.nlink() <= 1 || seen_inodes.insert((meta.dev(), meta.ino())) {
+            node.size = meta.len();
+        }
+        progress.files.fetch_add(1, Ordering::Relaxed);
+        progress.bytes.fetch_add(node.size, Ordering::Relaxed);
+        return Some(node);
+    }
+    //mount points show up as empty directories
+    if meta.dev() != device {
+        return Some(node);
+    }
+
+    for entry in fs::read_dir(path).into_iter().flatten().flatten() {
+        let Ok(child_meta) = entry.metadata() else {
+            continue;
+        };
+        let child = scan_node(&entry.path(), &child_meta, device, seen_inodes, progress)?;
+        node.size += child.size;
+        node.children.push(child);
+    }
+    node.children
+        .sort_by(|a, b| b.size.cmp(&a.size).then(a.name.cmp(&b.name)));
+    Some(node)
+}
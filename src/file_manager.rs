@@ -1,32 +1,120 @@
+use crate::bookmarks::Bookmarks;
+use crate::cli::{Options, PickMode};
+use crate::columns::Column;
+use crate::config::Config;
+use crate::dir_size::DirSizes;
+use crate::file_operations::{self, ConflictPolicy};
+use crate::filter::Filter;
+use crate::jobs::{Job, JobContext, JobManager, JobStatus};
+use crate::journal::{Action, Journal, JournalEntry};
+use crate::keymap::Keymap;
+use crate::listing_cache::ListingCache;
+use crate::navigation::{History, RecentDirs};
+use crate::trash::{self, Trash, TrashEntry};
+use crate::util;
+use crate::watcher::DirWatcher;
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
-use std::collections::HashSet;
-use std::fs::{DirEntry, create_dir};
+use std::collections::{HashMap, HashSet};
+use std::fs::DirEntry;
 use std::io::{Error, ErrorKind};
 use std::path::{Path, PathBuf};
 use std::{fs, io};
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Sorting {
     Unsorted,
+    #[serde(rename = "size_descending")]
     SortedBySizeDescending,
+    #[serde(rename = "size_ascending")]
     SortedBySizeAscending,
+    #[serde(rename = "name_descending")]
     SortedByNameDescending,
+    #[serde(rename = "name_ascending")]
     SortedByNameAscending,
 }
 
+#[derive(Copy, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum SortDir {
     Unsorted,
     Start,
     End,
 }
 
+///decides if paste copies or moves the selection
+pub enum ClipboardMode {
+    Copy,
+    Cut,
+}
+
+///the view of a tab that is not shown, the one of the shown tab lives in the FileManager itself
+///and its highlighted row in the table of the explorer
+struct Tab {
+    dir: PathBuf,
+    sorting: Sorting,
+    show_hidden: bool,
+    dir_sorting: SortDir,
+    filter: Option<Filter>,
+    history: History,
+    //the listing of the tab, shown in the second pane
+    entries: Vec<DirEntry>,
+    //the highlighted row
+    selected: Option<usize>,
+}
+
 pub struct FileManager {
+    //absolute path of the listed directory
+    cwd: PathBuf,
     files: Vec<DirEntry>,
+    //entries of the current directory hidden by the filter
+    filtered_out: Vec<DirEntry>,
+    filter: Option<Filter>,
     pub num_files: usize,
     curr_sort: Sorting,
     pub show_hidden: bool,
+    //whether the fuzzy finder skips entries ignored by .gitignore files
+    pub respect_gitignore: bool,
     pub dir_sorting: SortDir,
+    //columns of the explorer table in the order they are shown
+    pub columns: Vec<Column>,
+    //show sizes in powers of 1000 (kB) instead of 1024 (KiB)
+    pub si_units: bool,
+    //calculate the recursive size of all listed directories
+    show_dir_sizes: bool,
+    dir_sizes: DirSizes,
     selection: HashSet<PathBuf>,
+    clipboard_mode: ClipboardMode,
+    journal: Journal,
+    trash: Trash,
+    jobs: JobManager,
+    //refreshes the listing when other processes change the current directory
+    watcher: DirWatcher,
+    history: History,
+    //show the expanded directories with their content below them
+    tree_view: bool,
+    //directories expanded in the tree view, kept when the listing is refreshed
+    expanded: HashSet<PathBuf>,
+    //the depth of every entry in the tree view, empty while the tree view is off
+    tree_depths: Vec<usize>,
+    //listings of the parent and child directories for the miller columns
+    listings: ListingCache,
+    //all tabs, None for the shown one
+    tabs: Vec<Option<Tab>>,
+    current_tab: usize,
+    recent_dirs: RecentDirs,
+    bookmarks: Bookmarks,
+    config: Config,
+    //the key bindings of the explorer and the popups
+    keymap: Keymap,
+    //refuse every operation that changes files
+    read_only: bool,
+    //what may be chosen in the picker mode, None outside of it
+    pick_mode: Option<PickMode>,
+    pick_multiple: bool,
+    //the paths chosen in the picker mode, empty if it was cancelled
+    picked: Vec<PathBuf>,
 
     error_queue: Vec<io::Error>,
 }
@@ -34,43 +122,325 @@ pub struct FileManager {
 impl FileManager {
     /// changes the content of the FileManager to the Files of the new path
     pub fn change_dir(&mut self, path_buf: PathBuf) {
-        let p: &Path = PathBuf::as_path(&path_buf);
-
-        if let Err(e) = std::env::set_current_dir(p) {
-            self.push_error(e);
-            return;
+        let previous_dir = self.cwd.clone();
+        if self.enter_dir(path_buf) && self.cwd != previous_dir {
+            self.history.visit(previous_dir);
         }
+    }
 
-        match fs::read_dir(".") {
-            Ok(entry_iter) => {
-                self.files.clear();
-                self.num_files = 0;
-                for entry in entry_iter.flatten() {
-                    if self.show_hidden
-                        || !entry
-                            .file_name()
-                            .to_str()
-                            .unwrap_or_default()
-                            .starts_with(".")
-                    {
-                        self.files.push(entry);
-                        self.num_files += 1;
-                    }
-                }
-                self.sort(self.curr_sort);
-            }
+    /// like change_dir but without touching the back/forward history.
+    /// Returns false if the directory could not be entered.
+    fn enter_dir(&mut self, path_buf: PathBuf) -> bool {
+        let dir = self.resolve(&path_buf);
+        let entry_iter = match fs::read_dir(&dir) {
+            Ok(entry_iter) => entry_iter,
             Err(e) => {
                 self.push_error(e);
+                return false;
             }
+        };
+        if dir != self.cwd {
+            //a filter only applies to the directory it was set in
+            self.filter = None;
+            if let Err(e) = self.recent_dirs.visit(&dir) {
+                self.push_error(e);
+            }
+            self.cwd = dir;
+        }
+
+        self.listings.clear();
+        self.files.clear();
+        self.tree_depths.clear();
+        self.filtered_out.clear();
+        for entry in entry_iter.flatten() {
+            if self.show_hidden
+                || !entry
+                    .file_name()
+                    .to_str()
+                    .unwrap_or_default()
+                    .starts_with(".")
+            {
+                self.files.push(entry);
+            }
+        }
+        self.apply_filter();
+        self.sort(self.curr_sort);
+        self.request_dir_sizes();
+        self.watch_tab_dirs();
+        true
+    }
+
+    ///absolute path of `path` without `.` and `..`, relative paths start at the current directory
+    pub fn resolve(&self, path: &Path) -> PathBuf {
+        util::normalize_path(&self.cwd.join(path))
+    }
+
+    pub fn current_dir(&self) -> PathBuf {
+        self.cwd.clone()
+    }
+
+    ///go back to the directory that was visited before the current one
+    pub fn go_back(&mut self) {
+        if let Some(target) = self.history.back(self.current_dir())
+            && !self.enter_dir(target)
+        {
+            self.history.revert(true);
+        }
+    }
+
+    ///return to the directory that was left with go_back
+    pub fn go_forward(&mut self) {
+        if let Some(target) = self.history.forward(self.current_dir())
+            && !self.enter_dir(target)
+        {
+            self.history.revert(false);
+        }
+    }
+
+    ///take the view of the shown tab out of the FileManager, `selected` is its highlighted row
+    fn park_tab(&mut self, selected: Option<usize>) -> Tab {
+        Tab {
+            dir: self.current_dir(),
+            sorting: self.curr_sort,
+            show_hidden: self.show_hidden,
+            dir_sorting: self.dir_sorting,
+            filter: self.filter.take(),
+            history: std::mem::replace(&mut self.history, History::new()),
+            entries: std::mem::take(&mut self.files),
+            selected,
+        }
+    }
+
+    ///show the view of a parked tab, returns its highlighted row
+    fn unpark_tab(&mut self, tab: Tab) -> Option<usize> {
+        self.cwd = tab.dir;
+        self.curr_sort = tab.sorting;
+        self.show_hidden = tab.show_hidden;
+        self.dir_sorting = tab.dir_sorting;
+        self.filter = tab.filter;
+        self.history = tab.history;
+        self.update();
+        tab.selected
+    }
+
+    //the tab functions take the highlighted row of the shown tab and return the one of the tab
+    //shown afterwards
+
+    ///open a new tab in the current directory and show it, with the same row highlighted
+    pub fn open_tab(&mut self, selected: Option<usize>) -> Option<usize> {
+        let parked = self.park_tab(selected);
+        let tab = Tab {
+            dir: parked.dir.clone(),
+            sorting: parked.sorting,
+            show_hidden: parked.show_hidden,
+            dir_sorting: parked.dir_sorting,
+            filter: None,
+            history: History::new(),
+            entries: Vec::new(),
+            selected,
+        };
+        self.tabs[self.current_tab] = Some(parked);
+        self.current_tab += 1;
+        self.tabs.insert(self.current_tab, None);
+        self.unpark_tab(tab)
+    }
+
+    ///close the shown tab and show its neighbour, the last tab cannot be closed
+    pub fn close_tab(&mut self, selected: Option<usize>) -> Option<usize> {
+        if self.tabs.len() <= 1 {
+            return selected;
+        }
+        self.tabs.remove(self.current_tab);
+        self.current_tab = self.current_tab.min(self.tabs.len() - 1);
+        match self.tabs[self.current_tab].take() {
+            Some(tab) => self.unpark_tab(tab),
+            None => selected,
+        }
+    }
+
+    ///show the tab at `index`
+    pub fn switch_tab(&mut self, index: usize, selected: Option<usize>) -> Option<usize> {
+        if index == self.current_tab {
+            return selected;
+        }
+        let Some(tab) = self.tabs.get_mut(index).and_then(Option::take) else {
+            return selected;
+        };
+        self.tabs[self.current_tab] = Some(self.park_tab(selected));
+        self.current_tab = index;
+        self.unpark_tab(tab)
+    }
+
+    pub fn current_tab(&self) -> usize {
+        self.current_tab
+    }
+
+    pub fn tab_count(&self) -> usize {
+        self.tabs.len()
+    }
+
+    ///the highlighted row of the parked tab at `index`
+    pub fn tab_selected(&self, index: usize) -> Option<usize> {
+        self.tabs.get(index)?.as_ref()?.selected
+    }
+
+    ///the listing of the tab at `index`
+    pub fn tab_entries(&self, index: usize) -> &[DirEntry] {
+        match self.tabs.get(index) {
+            Some(Some(tab)) => &tab.entries,
+            _ => &self.files,
+        }
+    }
+
+    ///watch the directory of the shown tab and the ones of the parked tabs
+    fn watch_tab_dirs(&mut self) {
+        let mut dirs = vec![self.cwd.clone()];
+        for tab in self.tabs.iter().flatten() {
+            if !dirs.contains(&tab.dir) {
+                dirs.push(tab.dir.clone());
+            }
+        }
+        if let Err(e) = self.watcher.watch(&dirs) {
+            self.push_error(e);
+        }
+    }
+
+    ///read the directories of the other tabs again, e.g. after a paste into the second pane
+    fn refresh_parked_tabs(&mut self) {
+        for tab in self.tabs.iter_mut().flatten() {
+            tab.entries = FileManager::read_listing(
+                &tab.dir,
+                tab.show_hidden,
+                tab.filter.as_ref(),
+                tab.sorting,
+                tab.dir_sorting,
+                &self.dir_sizes,
+            );
+        }
+    }
+
+    ///the sorted entries of a directory other than the current one, empty if it can't be read
+    fn read_listing(
+        dir: &Path,
+        show_hidden: bool,
+        filter: Option<&Filter>,
+        sorting: Sorting,
+        dir_sorting: SortDir,
+        dir_sizes: &DirSizes,
+    ) -> Vec<DirEntry> {
+        let Ok(entry_iter) = fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut entries: Vec<DirEntry> = entry_iter
+            .flatten()
+            .filter(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                (show_hidden || !name.starts_with('.'))
+                    && filter.is_none_or(|filter| filter.matches(&name))
+            })
+            .collect();
+        FileManager::sort_entries(&mut entries, sorting, dir_sorting, dir_sizes);
+        entries
+    }
+
+    ///list `dir` for the miller columns unless it is cached, see listing
+    pub fn load_listing(&mut self, dir: &Path) {
+        self.listings.load(dir, || {
+            FileManager::read_listing(
+                dir,
+                self.show_hidden,
+                None,
+                self.curr_sort,
+                self.dir_sorting,
+                &self.dir_sizes,
+            )
+        });
+    }
+
+    ///the listing of `dir` loaded with load_listing, sorted like the current directory
+    pub fn listing(&self, dir: &Path) -> &[DirEntry] {
+        self.listings.get(dir).unwrap_or_default()
+    }
+
+    ///the directory of every tab
+    pub fn tab_dirs(&self) -> Vec<PathBuf> {
+        self.tabs
+            .iter()
+            .map(|tab| match tab {
+                Some(tab) => tab.dir.clone(),
+                None => self.cwd.clone(),
+            })
+            .collect()
+    }
+
+    pub fn recent_dirs(&self) -> Vec<PathBuf> {
+        self.recent_dirs
+            .ranked()
+            .into_iter()
+            .map(|dir| dir.path.clone())
+            .collect()
+    }
+
+    ///save the current directory under the mark `letter`
+    pub fn set_mark(&mut self, letter: char) {
+        if !Bookmarks::is_valid(letter) {
+            self.push_error(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Invalid mark: {letter}"),
+            ));
+            return;
+        }
+        let dir = self.current_dir();
+        if let Err(e) = self.bookmarks.set(letter, dir) {
+            self.push_error(e);
+        }
+    }
+
+    ///change to the directory saved under the mark `letter`
+    pub fn jump_to_mark(&mut self, letter: char) {
+        match self.bookmarks.get(letter) {
+            Some(path) => self.change_dir(path.to_path_buf()),
+            None => self.push_error(Error::new(
+                ErrorKind::NotFound,
+                format!("Mark not set: {letter}"),
+            )),
         }
     }
 
-    pub fn current_dir(&self) -> io::Result<PathBuf> {
-        std::env::current_dir()
+    ///all marks ordered by their letter
+    pub fn marks(&self) -> Vec<(char, PathBuf)> {
+        self.bookmarks
+            .marks()
+            .map(|(letter, path)| (letter, path.to_path_buf()))
+            .collect()
+    }
+
+    ///move a mark to another letter
+    pub fn rename_mark(&mut self, from: char, to: char) {
+        if !Bookmarks::is_valid(to) {
+            return;
+        }
+        if let Err(e) = self.bookmarks.rename(from, to) {
+            self.push_error(e);
+        }
+    }
+
+    pub fn remove_mark(&mut self, letter: char) {
+        if let Err(e) = self.bookmarks.remove(letter) {
+            self.push_error(e);
+        }
+    }
+
+    ///remove a directory from the recent directories
+    pub fn forget_recent_dir(&mut self, path: &Path) {
+        if let Err(e) = self.recent_dirs.remove(path) {
+            self.push_error(e);
+        }
     }
 
     /// open file with system default program or change directory if path is a directory
     pub fn open_path(&mut self, path: &Path) {
+        let path = &self.resolve(path);
         if path.is_dir() {
             self.change_dir(path.to_path_buf());
         } else if let Err(e) = open::that_detached(path) {
@@ -84,63 +454,344 @@ impl FileManager {
     }
 
     ///creates and initializes a FileManager-struct
-    ///Calls change_dir on the CWD!
-    pub fn new() -> FileManager {
+    ///Lists the start directory of the command line options!
+    pub fn new(options: &Options) -> FileManager {
+        let (recent_dirs, recent_dirs_error) = RecentDirs::load_default();
+        let (bookmarks, bookmarks_error) = Bookmarks::load_default();
+        let (mut config, config_errors) = match &options.config {
+            Some(file) => Config::load(file.clone()),
+            None => Config::load_default(),
+        };
+        //the command line takes precedence over the config file
+        config.show_hidden |= options.show_hidden;
+        if let Some(sorting) = options.sorting {
+            config.sorting = sorting;
+        }
+        let mut fm = FileManager::with_state(
+            options.start_dir.clone(),
+            config,
+            recent_dirs,
+            bookmarks,
+            Trash::of_user(),
+        );
+        fm.read_only = options.read_only;
+        fm.pick_mode = options.pick;
+        fm.pick_multiple = options.multiple;
+        for e in [recent_dirs_error, bookmarks_error].into_iter().flatten() {
+            fm.push_error(e);
+        }
+        let (keymap, keymap_errors) = Keymap::load_default();
+        fm.keymap = keymap;
+        for e in config_errors.into_iter().chain(keymap_errors) {
+            fm.push_error(e);
+        }
+        fm
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn keymap(&self) -> &Keymap {
+        &self.keymap
+    }
+
+    ///remember the sorting and hidden files of the current tab, they are written to the config
+    ///file if it asks for it
+    pub fn save_preferences(&mut self) {
+        if let Err(e) =
+            self.config
+                .save_preferences(self.show_hidden, self.curr_sort, self.dir_sorting)
+        {
+            self.push_error(e);
+        }
+    }
+
+    ///a FileManager listing the absolute `dir` with the preferences of `config`, recent
+    ///directories and marks are kept in the given lists and deleted files go to `trash`
+    pub fn with_state(
+        dir: PathBuf,
+        config: Config,
+        recent_dirs: RecentDirs,
+        bookmarks: Bookmarks,
+        trash: Trash,
+    ) -> FileManager {
+        let (watcher, watcher_error) = DirWatcher::new();
         let mut fm: FileManager = FileManager {
+            cwd: util::normalize_path(&dir),
             files: Vec::new(),
+            filtered_out: Vec::new(),
+            filter: None,
             num_files: 0,
-            curr_sort: Sorting::Unsorted,
-            show_hidden: false,
-            dir_sorting: SortDir::Unsorted,
+            curr_sort: config.sorting,
+            show_hidden: config.show_hidden,
+            respect_gitignore: config.respect_gitignore,
+            dir_sorting: config.dir_sorting,
+            columns: vec![Column::Name, Column::Size],
+            si_units: config.si_units,
+            show_dir_sizes: false,
+            dir_sizes: DirSizes::new(),
             selection: HashSet::new(),
+            clipboard_mode: ClipboardMode::Copy,
+            journal: Journal::new(),
+            trash,
+            jobs: JobManager::new(),
+            watcher,
+            history: History::new(),
+            tree_view: false,
+            expanded: HashSet::new(),
+            tree_depths: Vec::new(),
+            listings: ListingCache::new(),
+            tabs: vec![None],
+            current_tab: 0,
+            recent_dirs,
+            bookmarks,
+            config,
+            keymap: Keymap::default_keys(),
+            read_only: false,
+            pick_mode: None,
+            pick_multiple: false,
+            picked: Vec::new(),
             error_queue: Vec::new(),
         };
-        fm.change_dir(PathBuf::from("."));
+        if let Some(e) = watcher_error {
+            fm.push_error(e);
+        }
+        fm.update();
+        //the start directory counts as visited
+        let dir = fm.current_dir();
+        if let Err(e) = fm.recent_dirs.visit(&dir) {
+            fm.push_error(e);
+        }
         fm
     }
 
+    ///the entries of the current directory, without the ones hidden by the filter
     pub fn get_entries(&self) -> &Vec<DirEntry> {
         &self.files
     }
 
+    ///number of entries in the current directory, including the ones hidden by the filter
+    pub fn total_entries(&self) -> usize {
+        self.files.len() + self.filtered_out.len()
+    }
+
+    ///narrow the listing to the entries matching `filter`, None shows all entries again
+    pub fn set_filter(&mut self, filter: Option<Filter>) {
+        self.filter = filter;
+        if self.tree_view {
+            //the listing holds the expanded children too, only the top level is filtered
+            self.enter_dir(PathBuf::from("."));
+            return;
+        }
+        self.files.append(&mut self.filtered_out);
+        self.apply_filter();
+        self.sort(self.curr_sort);
+    }
+
+    pub fn filter(&self) -> Option<&Filter> {
+        self.filter.as_ref()
+    }
+
+    fn apply_filter(&mut self) {
+        if let Some(filter) = &self.filter {
+            let (visible, hidden) = std::mem::take(&mut self.files)
+                .into_iter()
+                .partition(|entry| filter.matches(&entry.file_name().to_string_lossy()));
+            self.files = visible;
+            self.filtered_out = hidden;
+        }
+        self.num_files = self.files.len();
+    }
+
     pub fn sort(&mut self, sort_mode: Sorting) {
+        //the miller columns are listed again with the new order
+        self.listings.clear();
+        //only the top level is sorted here, the expanded directories are listed in order again
+        let mut depths = std::mem::take(&mut self.tree_depths).into_iter();
+        if depths.len() > 0 {
+            self.files.retain(|_| depths.next() == Some(0));
+        }
+        FileManager::sort_entries(
+            &mut self.files,
+            sort_mode,
+            self.dir_sorting,
+            &self.dir_sizes,
+        );
+        self.curr_sort = sort_mode;
+        self.expand_tree();
+    }
+
+    ///insert the content of the expanded directories below them. Only expanded directories are
+    ///read, so the size of the whole tree doesn't matter
+    fn expand_tree(&mut self) {
+        if !self.tree_view {
+            return;
+        }
+        let mut files = Vec::new();
+        let mut depths = Vec::new();
+        for entry in std::mem::take(&mut self.files) {
+            self.push_tree_node(entry, 0, &mut files, &mut depths);
+        }
+        self.files = files;
+        self.tree_depths = depths;
+        self.num_files = self.files.len();
+    }
+
+    fn push_tree_node(
+        &self,
+        entry: DirEntry,
+        depth: usize,
+        files: &mut Vec<DirEntry>,
+        depths: &mut Vec<usize>,
+    ) {
+        let path = entry.path();
+        files.push(entry);
+        depths.push(depth);
+        if !self.expanded.contains(&path) {
+            return;
+        }
+        let children = FileManager::read_listing(
+            &path,
+            self.show_hidden,
+            None,
+            self.curr_sort,
+            self.dir_sorting,
+            &self.dir_sizes,
+        );
+        for child in children {
+            self.push_tree_node(child, depth + 1, files, depths);
+        }
+    }
+
+    pub fn toggle_tree_view(&mut self) {
+        self.tree_view = !self.tree_view;
+        self.update();
+    }
+
+    pub fn is_tree_view(&self) -> bool {
+        self.tree_view
+    }
+
+    ///expand a collapsed directory of the tree view or collapse an expanded one
+    pub fn toggle_expanded(&mut self, path: &Path) {
+        let path = self.resolve(path);
+        if !self.expanded.remove(&path) {
+            self.expanded.insert(path);
+        }
+        self.update();
+    }
+
+    pub fn is_expanded(&self, path: &Path) -> bool {
+        self.expanded.contains(&self.resolve(path))
+    }
+
+    ///the depth of every entry in the tree view, 0 for the content of the current directory.
+    ///Empty while the tree view is off
+    pub fn tree_depths(&self) -> &[usize] {
+        &self.tree_depths
+    }
+
+    fn sort_entries(
+        entries: &mut [DirEntry],
+        sort_mode: Sorting,
+        dir_sorting: SortDir,
+        dir_sizes: &DirSizes,
+    ) {
         match sort_mode {
             //TODO!!!!!!!
-            Sorting::SortedBySizeDescending => self.files.sort_by(|b, a| {
-                let a_meta = a.metadata().map(|m| m.len()).unwrap_or_default();
-                let b_meta = b.metadata().map(|m| m.len()).unwrap_or_default();
-                a_meta.cmp(&b_meta)
-            }),
-            Sorting::SortedBySizeAscending => self.files.sort_by(|a, b| {
-                let a_meta = a.metadata().map(|m| m.len()).unwrap_or_default();
-                let b_meta = b.metadata().map(|m| m.len()).unwrap_or_default();
-                a_meta.cmp(&b_meta)
+            Sorting::SortedBySizeDescending => entries.sort_by_cached_key(|entry| {
+                std::cmp::Reverse(FileManager::entry_size(entry, dir_sizes))
             }),
-            Sorting::SortedByNameDescending => self.files.sort_by(|b, a| {
+            Sorting::SortedBySizeAscending => {
+                entries.sort_by_cached_key(|entry| FileManager::entry_size(entry, dir_sizes))
+            }
+            Sorting::SortedByNameDescending => entries.sort_by(|b, a| {
                 let a_string = a.file_name().into_string().unwrap_or_default();
                 let b_string = b.file_name().into_string().unwrap_or_default();
                 a_string.cmp(&b_string)
             }),
-            Sorting::SortedByNameAscending => self.files.sort_by(|a, b: &DirEntry| {
+            Sorting::SortedByNameAscending => entries.sort_by(|a, b: &DirEntry| {
                 let a_string = a.file_name().into_string().unwrap_or_default();
                 let b_string = b.file_name().into_string().unwrap_or_default();
                 a_string.cmp(&b_string)
             }),
             _ => {}
         };
-        self.curr_sort = sort_mode;
-        match self.dir_sorting {
+        match dir_sorting {
             SortDir::Start => {
-                self.files.sort_by(FileManager::sort_dir_to_start);
+                entries.sort_by(FileManager::sort_dir_to_start);
             }
             SortDir::End => {
-                self.files
-                    .sort_by(|b, a| FileManager::sort_dir_to_start(a, b));
+                entries.sort_by(|b, a| FileManager::sort_dir_to_start(a, b));
             }
             SortDir::Unsorted => {}
         }
     }
 
+    ///size of a file or the calculated size of a directory, 0 if it isn't known (yet)
+    fn entry_size(entry: &DirEntry, dir_sizes: &DirSizes) -> u64 {
+        match entry.metadata() {
+            Ok(meta) if meta.is_dir() => dir_sizes.get(&entry.path()).unwrap_or_default(),
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        }
+    }
+
+    ///the calculated recursive size of the directory at `path`
+    pub fn dir_size(&self, path: &Path) -> Option<u64> {
+        self.dir_sizes.get(&self.resolve(path))
+    }
+
+    ///true while the size of the directory at `path` is calculated
+    pub fn dir_size_pending(&self, path: &Path) -> bool {
+        self.dir_sizes.is_pending(&self.resolve(path))
+    }
+
+    ///switch the calculation of directory sizes on or off, switching it on recalculates all
+    pub fn toggle_dir_sizes(&mut self) {
+        self.show_dir_sizes = !self.show_dir_sizes;
+        self.dir_sizes.clear();
+        self.request_dir_sizes();
+    }
+
+    ///queue the directories of the listing for the size calculation
+    fn request_dir_sizes(&mut self) {
+        if !self.show_dir_sizes {
+            return;
+        }
+        let dirs = self
+            .files
+            .iter()
+            .chain(&self.filtered_out)
+            .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+            .map(DirEntry::path)
+            .collect();
+        self.dir_sizes.request(dirs);
+    }
+
+    ///take the finished directory sizes, sorting by size is updated with them.
+    ///Returns true if there were any
+    pub fn poll_dir_sizes(&mut self) -> bool {
+        if !self.dir_sizes.poll() {
+            return false;
+        }
+        if matches!(
+            self.curr_sort,
+            Sorting::SortedBySizeAscending | Sorting::SortedBySizeDescending
+        ) {
+            self.sort(self.curr_sort);
+        }
+        true
+    }
+
+    ///the cached sizes of the changed paths and their parent directories are outdated
+    fn invalidate_dir_sizes(&mut self, changed: &[PathBuf]) {
+        if self.show_dir_sizes {
+            self.dir_sizes.invalidate(changed);
+        }
+    }
+
     fn sort_dir_to_start(entry1: &DirEntry, entry2: &DirEntry) -> Ordering {
         if entry1.file_type().map(|e| e.is_dir()).unwrap_or(false) {
             Ordering::Less
@@ -153,215 +804,324 @@ impl FileManager {
 
     pub fn clear_selection(&mut self) {
         self.selection.clear();
+        self.clipboard_mode = ClipboardMode::Copy;
     }
 
     ///add file to selection
     pub fn add_to_selection(&mut self, pb: PathBuf) {
-        match std::path::absolute(pb) {
-            Ok(full_path) => {
-                self.selection.insert(full_path);
-            }
-            Err(e) => {
-                self.push_error(e);
-            }
-        }
+        let full_path = self.resolve(&pb);
+        self.selection.insert(full_path);
     }
 
     ///remove file from selection
     pub fn remove_from_selection(&mut self, pb: PathBuf) {
-        match std::path::absolute(pb) {
-            Ok(full_path) => {
-                self.selection.remove(&full_path);
-            }
-            Err(e) => {
-                self.push_error(e);
-            }
+        let full_path = self.resolve(&pb);
+        self.selection.remove(&full_path);
+    }
+
+    ///all selected paths
+    pub fn get_selection(&self) -> Vec<PathBuf> {
+        self.selection.iter().cloned().collect()
+    }
+
+    pub fn is_selected(&self, path: &Path) -> bool {
+        self.selection.contains(&self.resolve(path))
+    }
+
+    ///move all selected files into the trash, runs as a background job
+    pub fn delete_selection(&mut self) {
+        if !self.check_writable() {
+            return;
         }
+        let selection: Vec<PathBuf> = self.selection.drain().collect();
+        self.trash_paths(selection);
     }
 
-    pub fn is_selected(&self, path: &PathBuf) -> bool {
-        match std::path::absolute(path) {
-            Ok(full_path) => self.selection.contains(&full_path),
-            Err(_) => false,
+    ///delete all selected files without moving them into the trash, runs as a background job
+    pub fn delete_selection_permanently(&mut self) {
+        if !self.check_writable() {
+            return;
         }
+        let selection: Vec<PathBuf> = self.selection.drain().collect();
+        self.delete_paths_permanently(selection);
     }
 
-    pub fn delete_selection(&mut self) {
-        let selection_clone = self.selection.clone();
-        for dest in selection_clone {
-            if let Err(e) = self.delete(&dest) {
-                self.push_error(e);
+    ///move the files to the trash, runs as a background job.
+    ///Returns false if the job was refused
+    pub fn trash_paths(&mut self, paths: Vec<PathBuf>) -> bool {
+        if !self.check_writable() {
+            return false;
+        }
+        let trash = self.trash.clone();
+        self.jobs.spawn(
+            format!("Moving {} item(s) to the trash", paths.len()),
+            paths.clone(),
+            move |ctx| file_operations::trash_all(&paths, &trash, ctx),
+        );
+        true
+    }
+
+    ///delete the files without moving them into the trash, runs as a background job.
+    ///Returns false if the job was refused
+    pub fn delete_paths_permanently(&mut self, paths: Vec<PathBuf>) -> bool {
+        if !self.check_writable() {
+            return false;
+        }
+        self.jobs.spawn(
+            format!("Deleting {} item(s)", paths.len()),
+            paths.clone(),
+            move |ctx| file_operations::delete_all(&paths, ctx),
+        );
+        true
+    }
+
+    ///list the content of the trash
+    pub fn trash_entries(&mut self) -> Vec<TrashEntry> {
+        let (entries, errors) = self.trash.list();
+        for e in errors {
+            self.push_error(e);
+        }
+        entries
+    }
+
+    pub fn restore_from_trash(&mut self, entry: &TrashEntry) {
+        if !self.check_writable() {
+            return;
+        }
+        if let Err(e) = trash::restore(entry) {
+            self.push_error(e);
+        }
+    }
+
+    ///delete a file from the trash permanently
+    pub fn purge_from_trash(&mut self, entry: &TrashEntry) {
+        if !self.check_writable() {
+            return;
+        }
+        if let Err(e) = trash::purge(entry) {
+            self.push_error(e);
+        }
+    }
+
+    ///returns all selected entries whose name already exists in `target`
+    pub fn paste_conflicts(&self, target: &Path) -> Vec<PathBuf> {
+        let cut = self.is_cut();
+        let mut conflicts: Vec<PathBuf> = self
+            .selection
+            .iter()
+            .filter(|src| !(src.is_dir() && target.starts_with(src)))
+            .filter(|src| {
+                let Ok(name) = file_operations::entry_name(src) else {
+                    return false;
+                };
+                let dest = target.join(name);
+                //moving a file onto itself is a no-op
+                !(cut && dest == **src) && dest.symlink_metadata().is_ok()
+            })
+            .cloned()
+            .collect();
+        conflicts.sort();
+        conflicts
+    }
+
+    ///paste the content of copy_buffer into the directory `target`!
+    ///deep-copies directories, moves the files instead if the selection was cut.
+    ///Entries whose name already exists are handled according to `resolutions`,
+    ///conflicts without a resolution are skipped. Runs as a background job
+    pub fn paste(&mut self, target: &Path, resolutions: &HashMap<PathBuf, ConflictPolicy>) {
+        if !self.check_writable() {
+            return;
+        }
+        let target = target.to_path_buf();
+        let cut = self.is_cut();
+        let selection: Vec<PathBuf> = self.selection.iter().cloned().collect();
+        let resolutions = resolutions.clone();
+        let description = format!(
+            "{} {} item(s) into {}",
+            if cut { "Moving" } else { "Copying" },
+            selection.len(),
+            target.display()
+        );
+        //copying leaves the sources untouched
+        let mut changed = vec![target.clone()];
+        if cut {
+            changed.extend(selection.iter().cloned());
+        }
+        let trash = self.trash.clone();
+        self.jobs.spawn(description, changed, move |ctx| {
+            file_operations::paste(&selection, &target, cut, &resolutions, &trash, ctx)
+        });
+        self.clipboard_mode = ClipboardMode::Copy;
+    }
+
+    ///switch between copying and moving the selection on paste
+    pub fn toggle_cut(&mut self) {
+        self.clipboard_mode = match self.clipboard_mode {
+            ClipboardMode::Copy => ClipboardMode::Cut,
+            ClipboardMode::Cut => ClipboardMode::Copy,
+        };
+    }
+
+    pub fn is_cut(&self) -> bool {
+        matches!(self.clipboard_mode, ClipboardMode::Cut)
+    }
+
+    ///rename a single file or folder inside its directory
+    pub fn rename(&mut self, path: &Path, new_name: &str) {
+        if !self.check_writable() {
+            return;
+        }
+        if new_name.is_empty() || new_name.contains('/') || new_name == "." || new_name == ".." {
+            self.push_error(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Invalid filename: {new_name}"),
+            ));
+            return;
+        }
+        let from = self.resolve(path);
+        let to = from.with_file_name(new_name);
+        if to == from {
+            return;
+        }
+        if to.symlink_metadata().is_ok() {
+            self.push_error(Error::new(
+                ErrorKind::AlreadyExists,
+                format!("Cannot rename, {} already exists", to.display()),
+            ));
+            return;
+        }
+        match fs::rename(&from, &to) {
+            Ok(()) => {
+                if self.selection.remove(&from) {
+                    self.selection.insert(to.clone());
+                }
+                self.journal.record(
+                    format!("Renamed {} to {}", from.display(), new_name),
+                    vec![Action::Moved { from, to }],
+                );
             }
+            Err(e) => self.push_error(e),
         }
-        self.selection.clear();
         self.update();
     }
 
-    pub fn delete(&mut self, dest: &PathBuf) -> io::Result<()> {
-        if dest.is_dir() {
-            fs::remove_dir_all(dest)?;
-            Ok(())
-        } else if dest.is_file() || dest.is_symlink() {
-            fs::remove_file(dest)?;
-            Ok(())
-        } else {
-            panic!();
+    ///validates the edited names of a bulk rename and returns the renames that have to be done.
+    ///`new_names` contains one path per source, relative paths are resolved against the cwd
+    pub fn plan_bulk_rename(
+        &self,
+        sources: &[PathBuf],
+        new_names: &[String],
+    ) -> io::Result<Vec<(PathBuf, PathBuf)>> {
+        if sources.len() != new_names.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Bulk rename expected {} lines but got {}, lines must not be added or removed",
+                    sources.len(),
+                    new_names.len()
+                ),
+            ));
         }
-    }
 
-    ///paste the content of copy_buffer into the current directory!
-    ///deep-copies directories
-    pub fn paste(&mut self) {
-        let current_dir = match std::path::absolute(PathBuf::from(".")) {
-            Ok(dir) => dir,
-            Err(e) => {
-                self.push_error(e);
-                return;
+        let mut targets: HashSet<PathBuf> = HashSet::new();
+        let mut plan = Vec::new();
+        for (src, new_name) in sources.iter().zip(new_names) {
+            if new_name.trim().is_empty() {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Bulk rename: missing new name for {}", src.display()),
+                ));
             }
-        };
+            let target = self.resolve(Path::new(new_name));
+            if !targets.insert(target.clone()) {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Bulk rename: duplicate name {new_name}"),
+                ));
+            }
+            if target != *src {
+                plan.push((src.clone(), target));
+            }
+        }
 
-        let selection = self.selection.clone();
-        for src in &selection {
-            //check if src dir gets copied into itself
-            if src.is_dir() && current_dir.starts_with(src) {
-                continue;
+        //targets may only exist if they get renamed themselves
+        for (_, target) in &plan {
+            if target.symlink_metadata().is_ok() && !sources.contains(target) {
+                return Err(Error::new(
+                    ErrorKind::AlreadyExists,
+                    format!("Bulk rename: {} already exists", target.display()),
+                ));
             }
+        }
+        Ok(plan)
+    }
 
-            if src.is_file() {
-                let file_name = match src.file_name() {
-                    Some(name) => name,
-                    None => {
-                        self.push_error(Error::new(
-                            ErrorKind::InvalidInput,
-                            format!("Invalid filename: {}", src.display()),
-                        ));
-                        continue;
-                    }
-                };
-                let file_name_str = match file_name.to_str() {
-                    Some(s) => s,
-                    None => {
-                        self.push_error(Error::new(
-                            ErrorKind::InvalidInput,
-                            format!("Invalid filename: {}", src.display()),
-                        ));
-                        continue;
-                    }
-                };
-                if let Err(e) = fs::copy(src, PathBuf::from(file_name_str)) {
-                    self.push_error(e);
+    ///applies a plan created by plan_bulk_rename.
+    ///All sources are moved to temporary names first, so swaps and cycles like a→b, b→a work
+    pub fn bulk_rename(&mut self, plan: Vec<(PathBuf, PathBuf)>) {
+        if !self.check_writable() {
+            return;
+        }
+        let mut actions = Vec::new();
+        let mut staged = Vec::new();
+        for (index, (from, to)) in plan.into_iter().enumerate() {
+            let temp_name = format!(".pathexplorer-rename-{}-{}", std::process::id(), index);
+            let temp = from.with_file_name(temp_name);
+            match fs::rename(&from, &temp) {
+                Ok(()) => {
+                    actions.push(Action::Moved {
+                        from: from.clone(),
+                        to: temp.clone(),
+                    });
+                    staged.push((from, temp, to));
                 }
+                Err(e) => self.push_error(e),
             }
-            //copying the directory and recursively copy it's content into the new directory
-            else if src.is_dir() {
-                let src_folder_name = match src.file_name() {
-                    Some(name) => match name.to_str() {
-                        Some(name_str) => name_str,
-                        None => {
-                            self.push_error(Error::new(
-                                ErrorKind::InvalidInput,
-                                format!("Invalid directory name: {}", src.display()),
-                            ));
-                            continue;
-                        }
-                    },
-                    None => {
-                        self.push_error(Error::new(
-                            ErrorKind::InvalidInput,
-                            format!("Invalid directory name: {}", src.display()),
-                        ));
-                        continue;
-                    }
-                };
+        }
 
-                let dest_folder = PathBuf::from(src_folder_name);
-                if let Err(e) = create_dir(&dest_folder) {
-                    self.push_error(e);
-                    continue;
+        let count = staged.len();
+        for (from, temp, to) in staged {
+            let result = if to.symlink_metadata().is_ok() {
+                Err(Error::new(
+                    ErrorKind::AlreadyExists,
+                    format!("Cannot rename, {} already exists", to.display()),
+                ))
+            } else {
+                fs::rename(&temp, &to)
+            };
+            match result {
+                Ok(()) => {
+                    if self.selection.remove(&from) {
+                        self.selection.insert(to.clone());
+                    }
+                    actions.push(Action::Moved { from: temp, to });
                 }
-
-                let mut stack: Vec<PathBuf> = Vec::new(); //contains relative paths within the source directory
-                stack.push(PathBuf::from(".")); //start with the root of the source directory
-
-                loop {
-                    let current_relative_path = match stack.pop() {
-                        None => break, //stack is empty
-                        Some(path) => path,
-                    };
-
-                    let current_src_path = src.join(&current_relative_path);
-                    let entry_iter = match fs::read_dir(&current_src_path) {
-                        Ok(iter) => iter,
-                        Err(e) => {
-                            self.push_error(e);
-                            continue;
-                        }
-                    };
-
-                    for entry_res in entry_iter {
-                        let entry = match entry_res {
-                            Ok(entry) => entry,
-                            Err(e) => {
-                                self.push_error(e);
-                                continue;
-                            }
-                        };
-                        let file_type = match entry.file_type() {
-                            Ok(file_type) => file_type,
-                            Err(e) => {
-                                self.push_error(e);
-                                continue;
-                            }
-                        };
-
-                        let relative_entry_path = current_relative_path.join(entry.file_name());
-                        let src_entry = src.join(&relative_entry_path);
-                        let dest_entry = dest_folder.join(&relative_entry_path);
-
-                        if file_type.is_dir() {
-                            if let Err(e) = create_dir(&dest_entry) {
-                                self.push_error(e);
-                                continue;
-                            }
-                            stack.push(relative_entry_path);
-                        } else if file_type.is_file() {
-                            if let Err(e) = fs::copy(src_entry, dest_entry) {
-                                self.push_error(e);
-                            }
-                        } else if file_type.is_symlink() {
-                            let link_target = match fs::read_link(&src_entry) {
-                                Ok(target) => target,
-                                Err(e) => {
-                                    self.push_error(e);
-                                    continue;
-                                }
-                            };
-                            #[cfg(unix)]
-                            {
-                                if let Err(e) = std::os::unix::fs::symlink(link_target, dest_entry)
-                                {
-                                    self.push_error(e);
-                                }
-                            }
-                            #[cfg(windows)]
-                            {
-                                let result = if src_entry.is_dir() {
-                                    std::os::windows::fs::symlink_dir(link_target, dest_entry)
-                                } else {
-                                    std::os::windows::fs::symlink_file(link_target, dest_entry)
-                                };
-                                if let Err(e) = result {
-                                    self.push_error(e);
-                                }
-                            }
-                        }
+                Err(e) => {
+                    self.push_error(e);
+                    //put the file back to its old name
+                    match fs::rename(&temp, &from) {
+                        Ok(()) => actions.push(Action::Moved {
+                            from: temp,
+                            to: from,
+                        }),
+                        Err(e) => self.push_error(e),
                     }
                 }
             }
         }
+        self.journal
+            .record(format!("Renamed {count} item(s)"), actions);
         self.update();
     }
 
+    ///index of path in the current listing
+    pub fn index_of(&self, path: &Path) -> Option<usize> {
+        let path = self.resolve(path);
+        self.get_entries()
+            .iter()
+            .position(|entry| entry.path() == path)
+    }
+
     pub fn get_entry_at_index(&self, index: usize) -> Result<&DirEntry, Error> {
         let entry = self.get_entries().get(index);
         match entry {
@@ -371,29 +1131,233 @@ impl FileManager {
     }
 
     pub fn create_file(&mut self, path: PathBuf) {
-        if let Some(parent) = path.parent() {
-            if let Err(e) = fs::create_dir_all(parent) {
-                self.push_error(e);
-            }
+        if !self.check_writable() {
+            return;
         }
-        if let Err(e) = fs::File::create(path) {
+        let path = self.resolve(&path);
+        let created = FileManager::first_missing_ancestor(&path);
+        if let Some(parent) = path.parent()
+            && let Err(e) = fs::create_dir_all(parent)
+        {
             self.push_error(e);
         }
+        match fs::File::create(&path) {
+            Ok(_) => self.record_creation(created, &path),
+            Err(e) => self.push_error(e),
+        }
         self.update();
     }
 
     pub fn create_folder(&mut self, path: PathBuf) {
-        if let Err(e) = fs::create_dir_all(path) {
+        if !self.check_writable() {
+            return;
+        }
+        let path = self.resolve(&path);
+        let created = FileManager::first_missing_ancestor(&path);
+        match fs::create_dir_all(&path) {
+            Ok(()) => self.record_creation(created, &path),
+            Err(e) => self.push_error(e),
+        }
+        self.update();
+    }
+
+    ///returns the outermost directory (or the path itself) that has to be created for path
+    fn first_missing_ancestor(path: &Path) -> Option<PathBuf> {
+        let mut missing = None;
+        for ancestor in path.ancestors() {
+            if ancestor.as_os_str().is_empty() || ancestor.symlink_metadata().is_ok() {
+                break;
+            }
+            missing = Some(ancestor);
+        }
+        missing.map(Path::to_path_buf)
+    }
+
+    fn record_creation(&mut self, created: Option<PathBuf>, path: &Path) {
+        if let Some(created) = created {
+            self.journal.record(
+                format!("Created {}", path.display()),
+                vec![Action::Created(created)],
+            );
+        }
+    }
+
+    ///handle progress, errors and results of the background jobs.
+    ///Returns true if a job finished and the listing was updated
+    pub fn poll_jobs(&mut self) -> bool {
+        let (errors, finished) = self.jobs.poll();
+        for e in errors {
+            self.push_error(e);
+        }
+        if finished.is_empty() {
+            return false;
+        }
+        let mut changed = Vec::new();
+        for job in finished {
+            changed.extend(job.changed);
+            changed.extend(
+                job.actions
+                    .iter()
+                    .flat_map(Action::paths)
+                    .map(Path::to_path_buf),
+            );
+            self.journal.record(job.description, job.actions);
+        }
+        self.invalidate_dir_sizes(&changed);
+        self.update();
+        self.refresh_parked_tabs();
+        true
+    }
+
+    ///returns true if other processes changed the directory of a tab since the last call.
+    ///The other tabs are read again, the listing of the shown one is not updated yet
+    pub fn poll_watcher(&mut self) -> bool {
+        if !self.watcher.poll() {
+            return false;
+        }
+        self.refresh_parked_tabs();
+        true
+    }
+
+    pub fn jobs(&self) -> &[Job] {
+        self.jobs.jobs()
+    }
+
+    pub fn toggle_pause_job(&mut self, index: usize) {
+        self.jobs.toggle_pause(index);
+    }
+
+    pub fn cancel_job(&mut self, index: usize) {
+        self.jobs.cancel(index);
+    }
+
+    /// the number of jobs that are still running, paused or being cancelled
+    pub fn unfinished_jobs(&self) -> usize {
+        self.jobs()
+            .iter()
+            .filter(|job| {
+                matches!(
+                    job.status,
+                    JobStatus::Running | JobStatus::Paused | JobStatus::Cancelling
+                )
+            })
+            .count()
+    }
+
+    ///true while jobs are shown or directory sizes are calculated
+    pub fn is_busy(&self) -> bool {
+        !self.jobs().is_empty() || self.dir_sizes.is_busy()
+    }
+
+    pub fn cancel_all_jobs(&mut self) {
+        self.jobs.cancel_all();
+    }
+
+    pub fn journal(&self) -> &Journal {
+        &self.journal
+    }
+
+    ///undo the last `count` operations
+    pub fn undo(&mut self, count: usize) {
+        if !self.check_writable() {
+            return;
+        }
+        let changed: Vec<PathBuf> = self
+            .journal
+            .undo_entries()
+            .take(count)
+            .flat_map(JournalEntry::paths)
+            .map(Path::to_path_buf)
+            .collect();
+        let mut ctx = JobContext::detached();
+        self.journal.undo(count, &self.trash, &mut ctx);
+        for e in ctx.take_errors() {
             self.push_error(e);
         }
+        self.invalidate_dir_sizes(&changed);
         self.update();
     }
 
+    ///redo the last `count` undone operations
+    pub fn redo(&mut self, count: usize) {
+        if !self.check_writable() {
+            return;
+        }
+        let changed: Vec<PathBuf> = self
+            .journal
+            .redo_entries()
+            .take(count)
+            .flat_map(JournalEntry::paths)
+            .map(Path::to_path_buf)
+            .collect();
+        let mut ctx = JobContext::detached();
+        self.journal.redo(count, &self.trash, &mut ctx);
+        for e in ctx.take_errors() {
+            self.push_error(e);
+        }
+        self.invalidate_dir_sizes(&changed);
+        self.update();
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    ///false and an error in read-only mode, files may be changed otherwise
+    pub fn check_writable(&mut self) -> bool {
+        if self.read_only {
+            self.push_error(Error::new(
+                ErrorKind::PermissionDenied,
+                "Read-only mode, files are not changed",
+            ));
+        }
+        !self.read_only
+    }
+
+    pub fn pick_mode(&self) -> Option<PickMode> {
+        self.pick_mode
+    }
+
+    ///whether the picker mode chooses the whole selection
+    pub fn picks_multiple(&self) -> bool {
+        self.pick_multiple
+    }
+
+    ///choose `paths` in the picker mode. False and an error if one of them doesn't fit the mode
+    pub fn pick(&mut self, paths: Vec<PathBuf>) -> bool {
+        let Some(mode) = self.pick_mode else {
+            return false;
+        };
+        let unfit = paths.iter().find(|path| match mode {
+            PickMode::Files => path.is_dir(),
+            PickMode::Dirs => !path.is_dir(),
+            PickMode::Any => false,
+        });
+        if let Some(path) = unfit {
+            let expected = if mode == PickMode::Files {
+                "a file"
+            } else {
+                "a folder"
+            };
+            self.push_error(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{} is not {expected}", path.display()),
+            ));
+            return false;
+        }
+        self.picked = paths;
+        true
+    }
+
+    pub fn picked(&self) -> &[PathBuf] {
+        &self.picked
+    }
+
     pub fn take_errors(&mut self) -> Vec<io::Error> {
         std::mem::take(&mut self.error_queue)
     }
 
-    fn push_error(&mut self, error: io::Error) {
+    pub fn push_error(&mut self, error: io::Error) {
         self.error_queue.push(error);
     }
 
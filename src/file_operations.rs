@@ -0,0 +1,573 @@
+use crate::jobs::JobContext;
+use crate::journal::Action;
+use crate::trash::Trash;
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::fs::{self, create_dir};
+use std::io::{self, Error, ErrorKind, Read, Write};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+/// How to handle a paste onto a name that already exists in the target directory
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ConflictPolicy {
+    /// move the existing entry into the trash and replace it
+    Overwrite,
+    /// leave the existing entry untouched and don't paste
+    Skip,
+    /// paste with a free name like "name (1).ext"
+    Rename,
+    /// copy the content of a directory into the existing directory. Entries that exist on
+    /// both sides and aren't two directories are handled with the given policy, also when the
+    /// conflict itself isn't between two directories
+    Merge(MergeFiles),
+}
+
+/// How a merge handles the entries that exist on both sides and aren't two directories
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum MergeFiles {
+    Overwrite,
+    Skip,
+    Rename,
+}
+
+impl ConflictPolicy {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ConflictPolicy::Overwrite => "Overwrite",
+            ConflictPolicy::Skip => "Skip",
+            ConflictPolicy::Rename => "Rename",
+            ConflictPolicy::Merge(MergeFiles::Overwrite) => "Merge, overwrite files",
+            ConflictPolicy::Merge(MergeFiles::Skip) => "Merge, skip files",
+            ConflictPolicy::Merge(MergeFiles::Rename) => "Merge, rename files",
+        }
+    }
+}
+
+/// returns the first path of the form "name (n).ext" that doesn't exist yet
+pub fn unique_name(path: &Path) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default();
+    let extension = path.extension();
+    (1..)
+        .map(|n| {
+            let mut name = OsString::from(stem);
+            name.push(format!(" ({n})"));
+            if let Some(extension) = extension {
+                name.push(".");
+                name.push(extension);
+            }
+            path.with_file_name(name)
+        })
+        .find(|candidate| candidate.symlink_metadata().is_err())
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// copies the content of the directory `src` into the existing directory `dest`, moves it if
+/// `cut` is set and trashes the then empty `src`. Subdirectories that exist on both sides are
+/// merged the same way, other existing entries are handled with `files`.
+/// All changes are recorded in `actions`, replaced entries are moved into `trash`.
+pub fn merge(
+    src: &Path,
+    dest: &Path,
+    files: MergeFiles,
+    cut: bool,
+    trash: &Trash,
+    ctx: &mut JobContext,
+    actions: &mut Vec<Action>,
+) -> io::Result<()> {
+    for entry in fs::read_dir(src)? {
+        ctx.checkpoint()?;
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                ctx.error(e);
+                continue;
+            }
+        };
+        let src_entry = entry.path();
+        let mut dest_entry = dest.join(entry.file_name());
+
+        if let Ok(dest_meta) = dest_entry.symlink_metadata() {
+            let src_is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            if dest_meta.is_dir() && src_is_dir {
+                if let Err(e) = merge(&src_entry, &dest_entry, files, cut, trash, ctx, actions) {
+                    ctx.error(e);
+                }
+                continue;
+            }
+            match make_room(&dest_entry, files, trash, ctx, actions) {
+                Some(path) => dest_entry = path,
+                None => continue,
+            }
+        }
+
+        let result = if cut {
+            move_path(&src_entry, &dest_entry, ctx).map(|_| Action::Moved {
+                from: src_entry,
+                to: dest_entry,
+            })
+        } else {
+            let copied = match entry.file_type() {
+                Ok(file_type) if file_type.is_symlink() => {
+                    copy_symlink(&src_entry, &dest_entry).inspect(|_| ctx.file_done())
+                }
+                Ok(_) => copy_recursive(&src_entry, &dest_entry, ctx),
+                Err(e) => Err(e),
+            };
+            copied.map(|_| Action::Created(dest_entry))
+        };
+        match result {
+            Ok(action) => actions.push(action),
+            Err(e) => ctx.error(e),
+        }
+    }
+
+    //only remove src if everything was moved out of it
+    if cut && fs::read_dir(src)?.next().is_none() {
+        actions.push(Action::Trashed(trash.move_to_trash(src)?));
+    }
+    Ok(())
+}
+
+/// handles an entry that should go to the existing `dest` according to `files`. Returns the
+/// path the entry goes to, None if it is skipped
+fn make_room(
+    dest: &Path,
+    files: MergeFiles,
+    trash: &Trash,
+    ctx: &mut JobContext,
+    actions: &mut Vec<Action>,
+) -> Option<PathBuf> {
+    match files {
+        MergeFiles::Skip => None,
+        MergeFiles::Rename => Some(unique_name(dest)),
+        MergeFiles::Overwrite => match trash.move_to_trash(dest) {
+            Ok(trashed) => {
+                actions.push(Action::Trashed(trashed));
+                Some(dest.to_path_buf())
+            }
+            Err(e) => {
+                ctx.error(e);
+                None
+            }
+        },
+    }
+}
+
+/// name of the entry at `src`, used as the name of its copy or moved version
+pub fn entry_name(src: &Path) -> io::Result<&OsStr> {
+    src.file_name().ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("Invalid filename: {}", src.display()),
+        )
+    })
+}
+
+/// copies the file or directory at `src` to `dest`, deep-copies directories.
+/// Returns an error if `dest` could not be created at all, errors for single entries inside a
+/// directory are reported to `ctx` and the copy continues with the next entry.
+/// A cancelled copy is removed again, also the one of a directory.
+pub fn copy_recursive(src: &Path, dest: &Path, ctx: &mut JobContext) -> io::Result<()> {
+    if src.is_file() {
+        return copy_file(src, dest, ctx);
+    }
+    if !src.is_dir() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("Cannot copy {}", src.display()),
+        ));
+    }
+
+    //copying the directory and recursively copy it's content into the new directory
+    create_dir(dest)?;
+    ctx.file_done();
+    let result = copy_dir_content(src, dest, ctx);
+    if result.is_err() {
+        let _ = fs::remove_dir_all(dest);
+    }
+    result
+}
+
+/// copies the content of the directory `src` into the existing directory `dest`.
+/// Only returns an error if the job was cancelled
+fn copy_dir_content(src: &Path, dest: &Path, ctx: &mut JobContext) -> io::Result<()> {
+    let mut stack: Vec<PathBuf> = Vec::new(); //contains relative paths within the source directory
+    stack.push(PathBuf::from(".")); //start with the root of the source directory
+
+    loop {
+        ctx.checkpoint()?;
+        let current_relative_path = match stack.pop() {
+            None => break, //stack is empty
+            Some(path) => path,
+        };
+
+        let current_src_path = src.join(&current_relative_path);
+        let entry_iter = match fs::read_dir(&current_src_path) {
+            Ok(iter) => iter,
+            Err(e) => {
+                ctx.error(e);
+                continue;
+            }
+        };
+
+        for entry_res in entry_iter {
+            ctx.checkpoint()?;
+            let entry = match entry_res {
+                Ok(entry) => entry,
+                Err(e) => {
+                    ctx.error(e);
+                    continue;
+                }
+            };
+            let file_type = match entry.file_type() {
+                Ok(file_type) => file_type,
+                Err(e) => {
+                    ctx.error(e);
+                    continue;
+                }
+            };
+
+            let relative_entry_path = current_relative_path.join(entry.file_name());
+            let src_entry = src.join(&relative_entry_path);
+            let dest_entry = dest.join(&relative_entry_path);
+
+            if file_type.is_dir() {
+                if let Err(e) = create_dir(&dest_entry) {
+                    ctx.error(e);
+                    continue;
+                }
+                ctx.file_done();
+                stack.push(relative_entry_path);
+            } else if file_type.is_file() {
+                if let Err(e) = copy_file(&src_entry, &dest_entry, ctx) {
+                    ctx.error(e);
+                }
+            } else if file_type.is_symlink() {
+                match copy_symlink(&src_entry, &dest_entry) {
+                    Ok(()) => ctx.file_done(),
+                    Err(e) => ctx.error(e),
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// copies a single file in chunks, so the progress can be reported and the job can be paused
+/// or cancelled in the middle of large files. A cancelled copy is removed again.
+fn copy_file(src: &Path, dest: &Path, ctx: &mut JobContext) -> io::Result<()> {
+    const CHUNK_SIZE: usize = 1024 * 1024;
+
+    let mut reader = fs::File::open(src)?;
+    let permissions = reader.metadata()?.permissions();
+    let mut writer = fs::File::create(dest)?;
+    let mut buffer = vec![0; CHUNK_SIZE];
+    let result = (|| {
+        loop {
+            ctx.checkpoint()?;
+            let read = reader.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            writer.write_all(&buffer[..read])?;
+            ctx.bytes_done(read as u64);
+        }
+        writer.set_permissions(permissions)
+    })();
+    if result.is_err() {
+        drop(writer);
+        let _ = fs::remove_file(dest);
+    } else {
+        ctx.file_done();
+    }
+    result
+}
+
+/// creates a symlink at `dest` pointing to the same target as the symlink `src`
+fn copy_symlink(src: &Path, dest: &Path) -> io::Result<()> {
+    let link_target = fs::read_link(src)?;
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(link_target, dest)
+    }
+    #[cfg(windows)]
+    {
+        if src.is_dir() {
+            std::os::windows::fs::symlink_dir(link_target, dest)
+        } else {
+            std::os::windows::fs::symlink_file(link_target, dest)
+        }
+    }
+}
+
+/// moves `src` to `dest`. Uses a rename on the same filesystem, otherwise the entry gets
+/// copied, the copy gets verified and only then the source is deleted.
+pub fn move_path(src: &Path, dest: &Path, ctx: &mut JobContext) -> io::Result<()> {
+    if dest.symlink_metadata().is_ok() {
+        return Err(Error::new(
+            ErrorKind::AlreadyExists,
+            format!("Cannot move, {} already exists", dest.display()),
+        ));
+    }
+    if src.is_dir() && dest.starts_with(src) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("Cannot move {} into itself", src.display()),
+        ));
+    }
+
+    if same_filesystem(src, dest)? {
+        match fs::rename(src, dest) {
+            Err(e) if e.kind() == ErrorKind::CrossesDevices => {}
+            result => return result.inspect(|_| ctx.file_done()),
+        }
+    }
+
+    let src_meta = src.symlink_metadata()?;
+    if src_meta.is_symlink() {
+        copy_symlink(src, dest)?;
+        ctx.file_done();
+        return fs::remove_file(src);
+    }
+
+    let error_count = ctx.error_count();
+    let copied = copy_recursive(src, dest, ctx);
+    let verified = if ctx.error_count() != error_count {
+        Err(Error::other(format!(
+            "Could not copy {} completely, the source was kept",
+            src.display()
+        )))
+    } else {
+        copied.and_then(|_| verify_copy(src, dest))
+    };
+    if let Err(e) = verified {
+        //remove the incomplete copy, the source is still intact
+        let _ = remove_path(dest);
+        return Err(e);
+    }
+    remove_path(src)
+}
+
+/// checks that `dest` contains the same entries as `src` and that all files have the same size
+fn verify_copy(src: &Path, dest: &Path) -> io::Result<()> {
+    let mismatch = |path: &Path| {
+        Error::other(format!(
+            "Verification of the copy failed for {}",
+            path.display()
+        ))
+    };
+
+    let mut stack = vec![PathBuf::new()];
+    while let Some(relative_path) = stack.pop() {
+        let src_path = src.join(&relative_path);
+        let dest_path = dest.join(&relative_path);
+        let src_meta = src_path.symlink_metadata()?;
+        let dest_meta = dest_path
+            .symlink_metadata()
+            .map_err(|_| mismatch(&src_path))?;
+
+        if src_meta.file_type() != dest_meta.file_type() {
+            return Err(mismatch(&src_path));
+        }
+        if src_meta.is_file() && src_meta.len() != dest_meta.len() {
+            return Err(mismatch(&src_path));
+        }
+        if src_meta.is_dir() {
+            for entry in fs::read_dir(&src_path)? {
+                stack.push(relative_path.join(entry?.file_name()));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// removes a file, symlink or directory with all its content
+pub fn remove_path(path: &Path) -> io::Result<()> {
+    if path.symlink_metadata()?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// true if `src` and the directory that will contain `dest` are on the same filesystem
+pub fn same_filesystem(src: &Path, dest: &Path) -> io::Result<bool> {
+    let src_parent = src.parent().unwrap_or(src);
+    let dest_parent = dest.parent().unwrap_or(dest);
+    Ok(fs::metadata(src_parent)?.dev() == fs::metadata(dest_parent)?.dev())
+}
+
+/// pastes `selection` into `current_dir`, moves the entries instead of copying them if `cut` is
+/// set. Entries whose name already exists are handled according to `resolutions`, conflicts
+/// without a resolution are skipped, replaced entries are moved into `trash`. Work function of
+/// the paste job, returns the journal description and actions.
+pub fn paste(
+    selection: &[PathBuf],
+    current_dir: &Path,
+    cut: bool,
+    resolutions: &HashMap<PathBuf, ConflictPolicy>,
+    trash: &Trash,
+    ctx: &mut JobContext,
+) -> (String, Vec<Action>) {
+    for src in selection {
+        //a rename on the same filesystem doesn't touch the content
+        if cut && same_filesystem(src, &current_dir.join("_")).unwrap_or(false) {
+            ctx.add_single_to_totals();
+        } else {
+            ctx.add_to_totals(src);
+        }
+    }
+
+    let mut actions = Vec::new();
+    let mut count = 0;
+    for src in selection {
+        if ctx.checkpoint().is_err() {
+            break;
+        }
+        //check if src dir gets copied into itself
+        if src.is_dir() && current_dir.starts_with(src) {
+            if cut {
+                ctx.error(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Cannot move {} into itself", src.display()),
+                ));
+            }
+            continue;
+        }
+
+        let mut dest = match entry_name(src) {
+            Ok(name) => current_dir.join(name),
+            Err(e) => {
+                ctx.error(e);
+                continue;
+            }
+        };
+        if cut && dest == *src {
+            continue;
+        }
+
+        if dest.symlink_metadata().is_ok() {
+            let policy = match resolutions.get(src) {
+                Some(policy) => *policy,
+                None => {
+                    ctx.error(Error::new(
+                        ErrorKind::AlreadyExists,
+                        format!("Skipped {}, it already exists", dest.display()),
+                    ));
+                    continue;
+                }
+            };
+            let files = match policy {
+                ConflictPolicy::Overwrite => MergeFiles::Overwrite,
+                ConflictPolicy::Skip => MergeFiles::Skip,
+                ConflictPolicy::Rename => MergeFiles::Rename,
+                //the source itself can't be merged into
+                ConflictPolicy::Merge(_) if dest == *src => continue,
+                ConflictPolicy::Merge(files) if src.is_dir() && dest.is_dir() => {
+                    match merge(src, &dest, files, cut, trash, ctx, &mut actions) {
+                        Ok(()) => count += 1,
+                        Err(e) => ctx.error(e),
+                    }
+                    continue;
+                }
+                ConflictPolicy::Merge(files) => files,
+            };
+            //the source itself can't be replaced
+            if dest == *src && files != MergeFiles::Rename {
+                continue;
+            }
+            match make_room(&dest, files, trash, ctx, &mut actions) {
+                Some(path) => dest = path,
+                None => continue,
+            }
+        }
+
+        if cut {
+            match move_path(src, &dest, ctx) {
+                Ok(()) => {
+                    count += 1;
+                    actions.push(Action::Moved {
+                        from: src.clone(),
+                        to: dest,
+                    })
+                }
+                Err(e) => ctx.error(e),
+            }
+        } else {
+            match copy_recursive(src, &dest, ctx) {
+                Ok(()) => {
+                    count += 1;
+                    actions.push(Action::Created(dest))
+                }
+                Err(e) => ctx.error(e),
+            }
+        }
+    }
+    let verb = if cut { "Moved" } else { "Copied" };
+    let description = format!("{} {} item(s) into {}", verb, count, current_dir.display());
+    (description, actions)
+}
+
+/// moves all entries of `selection` into the trash, work function of the trash job
+pub fn trash_all(
+    selection: &[PathBuf],
+    trash: &Trash,
+    ctx: &mut JobContext,
+) -> (String, Vec<Action>) {
+    for _ in selection {
+        ctx.add_single_to_totals();
+    }
+    let mut actions = Vec::new();
+    for path in selection {
+        if ctx.checkpoint().is_err() {
+            break;
+        }
+        match trash.move_to_trash(path) {
+            Ok(entry) => actions.push(Action::Trashed(entry)),
+            Err(e) => ctx.error(e),
+        }
+        ctx.file_done();
+    }
+    let description = format!("Moved {} item(s) to the trash", actions.len());
+    (description, actions)
+}
+
+/// deletes all entries of `selection` permanently, work function of the delete job.
+/// Nothing can be undone, so no actions are returned.
+pub fn delete_all(selection: &[PathBuf], ctx: &mut JobContext) -> (String, Vec<Action>) {
+    for path in selection {
+        ctx.add_to_totals(path);
+    }
+    let mut count = 0;
+    for path in selection {
+        match remove_recursive(path, ctx) {
+            Ok(()) => count += 1,
+            Err(e) => ctx.error(e),
+        }
+    }
+    (format!("Deleted {count} item(s)"), Vec::new())
+}
+
+/// removes `path` with all its content entry by entry, so the removal of large directories
+/// reports progress and can be paused or cancelled
+fn remove_recursive(path: &Path, ctx: &mut JobContext) -> io::Result<()> {
+    ctx.checkpoint()?;
+    let meta = path.symlink_metadata()?;
+    if meta.is_dir() {
+        for entry in fs::read_dir(path)? {
+            let result = entry.and_then(|entry| remove_recursive(&entry.path(), ctx));
+            match result {
+                Err(e) if e.kind() == ErrorKind::Interrupted => return Err(e),
+                Err(e) => ctx.error(e),
+                Ok(()) => {}
+            }
+        }
+        fs::remove_dir(path)?;
+    } else {
+        fs::remove_file(path)?;
+        ctx.bytes_done(meta.len());
+    }
+    ctx.file_done();
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use globset::{GlobBuilder, GlobMatcher};
+use regex::{Regex, RegexBuilder};
+
+/// How the pattern of a filter is matched against the file names
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum FilterMode {
+    /// the name contains the pattern
+    Substring,
+    /// the whole name matches a glob like `*.rs`
+    Glob,
+    /// the name contains a match of a regular expression
+    Regex,
+}
+
+impl FilterMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            FilterMode::Substring => "substring",
+            FilterMode::Glob => "glob",
+            FilterMode::Regex => "regex",
+        }
+    }
+
+    /// the next mode, used to cycle through the modes
+    pub fn next(&self) -> FilterMode {
+        match self {
+            FilterMode::Substring => FilterMode::Glob,
+            FilterMode::Glob => FilterMode::Regex,
+            FilterMode::Regex => FilterMode::Substring,
+        }
+    }
+}
+
+enum Matcher {
+    Substring(String),
+    Glob(GlobMatcher),
+    Regex(Regex),
+}
+
+/// Narrows the listing to the entries whose name matches the pattern.
+/// All modes ignore the case unless the pattern contains an uppercase character.
+pub struct Filter {
+    pattern: String,
+    mode: FilterMode,
+    case_sensitive: bool,
+    matcher: Matcher,
+}
+
+impl Filter {
+    /// compile `pattern`, fails for invalid globs and regular expressions
+    pub fn new(pattern: &str, mode: FilterMode) -> Result<Filter, String> {
+        let case_sensitive = pattern.chars().any(char::is_uppercase);
+        let matcher = match mode {
+            FilterMode::Substring => Matcher::Substring(if case_sensitive {
+                pattern.to_owned()
+            } else {
+                pattern.to_lowercase()
+            }),
+            FilterMode::Glob => GlobBuilder::new(pattern)
+                .case_insensitive(!case_sensitive)
+                .literal_separator(true)
+                .build()
+                .map(|glob| Matcher::Glob(glob.compile_matcher()))
+                .map_err(|e| e.kind().to_string())?,
+            FilterMode::Regex => RegexBuilder::new(pattern)
+                .case_insensitive(!case_sensitive)
+                .build()
+                .map(Matcher::Regex)
+                .map_err(|e| e.to_string())?,
+        };
+        Ok(Filter {
+            pattern: pattern.to_owned(),
+            mode,
+            case_sensitive,
+            matcher,
+        })
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    pub fn mode(&self) -> FilterMode {
+        self.mode
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        match &self.matcher {
+            Matcher::Substring(pattern) if self.case_sensitive => name.contains(pattern.as_str()),
+            Matcher::Substring(pattern) => name.to_lowercase().contains(pattern.as_str()),
+            Matcher::Glob(glob) => glob.is_match(name),
+            Matcher::Regex(regex) => regex.is_match(name),
+        }
+    }
+}
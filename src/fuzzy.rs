@@ -0,0 +1,193 @@
+//! Fuzzy matching of paths and the background walk that feeds the fuzzy finder.
+
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+/// the walk stops after this many entries, so huge trees like / don't eat all memory
+const MAX_ENTRIES: usize = 500_000;
+/// entries are sent to the ui in batches of this size
+const BATCH_SIZE: usize = 1024;
+
+const SCORE_MATCH: i64 = 16;
+const BONUS_CONSECUTIVE: i64 = 24;
+const BONUS_WORD_START: i64 = 20;
+const BONUS_FILE_NAME: i64 = 8;
+const PENALTY_GAP: i64 = 3;
+
+/// a candidate that matched the pattern
+pub struct FuzzyMatch {
+    pub score: i64,
+    /// char indices of the matched characters inside the candidate
+    pub positions: Vec<usize>,
+}
+
+/// Matches `pattern` against `candidate` as a subsequence. The pattern is case insensitive
+/// unless it contains an uppercase character. Consecutive matches, matches at the start of a
+/// word and matches inside the file name score higher, gaps between matches lower the score.
+pub fn fuzzy_match(pattern: &str, candidate: &str) -> Option<FuzzyMatch> {
+    let case_sensitive = pattern.chars().any(char::is_uppercase);
+    let normalize = |c: char| {
+        if case_sensitive {
+            c
+        } else {
+            c.to_ascii_lowercase()
+        }
+    };
+    let pattern: Vec<char> = pattern.chars().map(normalize).collect();
+    let chars: Vec<char> = candidate.chars().collect();
+    if pattern.is_empty() {
+        return Some(FuzzyMatch {
+            score: 0,
+            positions: Vec::new(),
+        });
+    }
+
+    //find the first position where the whole pattern matched
+    let mut pattern_index = 0;
+    let mut end = None;
+    for (i, c) in chars.iter().enumerate() {
+        if normalize(*c) == pattern[pattern_index] {
+            pattern_index += 1;
+            if pattern_index == pattern.len() {
+                end = Some(i);
+                break;
+            }
+        }
+    }
+    let end = end?;
+
+    //walk back from there to find the shortest window containing the match
+    let mut positions = Vec::with_capacity(pattern.len());
+    let mut pattern_index = pattern.len();
+    for i in (0..=end).rev() {
+        if normalize(chars[i]) == pattern[pattern_index - 1] {
+            positions.push(i);
+            pattern_index -= 1;
+            if pattern_index == 0 {
+                break;
+            }
+        }
+    }
+    positions.reverse();
+
+    let file_name_start = chars
+        .iter()
+        .rposition(|c| *c == '/')
+        .map_or(0, |slash| slash + 1);
+    let mut score = 0;
+    let mut previous: Option<usize> = None;
+    for &position in &positions {
+        score += SCORE_MATCH;
+        if position >= file_name_start {
+            score += BONUS_FILE_NAME;
+        }
+        if is_word_start(&chars, position) {
+            score += BONUS_WORD_START;
+        }
+        match previous {
+            Some(previous) if previous + 1 == position => score += BONUS_CONSECUTIVE,
+            Some(previous) => score -= PENALTY_GAP * (position - previous - 1).min(10) as i64,
+            None => {}
+        }
+        previous = Some(position);
+    }
+    //prefer shorter paths if everything else is equal
+    score -= (chars.len() / 8) as i64;
+
+    Some(FuzzyMatch { score, positions })
+}
+
+fn is_word_start(chars: &[char], position: usize) -> bool {
+    let Some(previous) = position.checked_sub(1).map(|i| chars[i]) else {
+        return true;
+    };
+    let current = chars[position];
+    matches!(previous, '/' | '_' | '-' | '.' | ' ')
+        || (previous.is_lowercase() && current.is_uppercase())
+}
+
+/// Walks the tree under a directory on a worker thread and sends the found paths, relative to
+/// the root, in batches. Dropping the walker stops the walk.
+pub struct TreeWalker {
+    receiver: Receiver<Vec<String>>,
+    stop: Arc<AtomicBool>,
+    finished: bool,
+}
+
+impl TreeWalker {
+    pub fn new(root: &Path, show_hidden: bool, respect_gitignore: bool) -> TreeWalker {
+        let (sender, receiver) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let root = root.to_path_buf();
+
+        thread::spawn(move || {
+            let walk = ignore::WalkBuilder::new(&root)
+                .hidden(!show_hidden)
+                .git_ignore(respect_gitignore)
+                .git_global(respect_gitignore)
+                .git_exclude(respect_gitignore)
+                .ignore(respect_gitignore)
+                .parents(respect_gitignore)
+                .require_git(false)
+                .build();
+
+            let mut batch = Vec::with_capacity(BATCH_SIZE);
+            //the root itself is skipped
+            for entry in walk.skip(1).flatten().take(MAX_ENTRIES) {
+                if thread_stop.load(Ordering::Relaxed) {
+                    return;
+                }
+                if let Ok(relative) = entry.path().strip_prefix(&root) {
+                    batch.push(relative.to_string_lossy().into_owned());
+                }
+                if batch.len() == BATCH_SIZE
+                    && sender
+                        .send(std::mem::replace(
+                            &mut batch,
+                            Vec::with_capacity(BATCH_SIZE),
+                        ))
+                        .is_err()
+                {
+                    return;
+                }
+            }
+            let _ = sender.send(batch);
+        });
+
+        TreeWalker {
+            receiver,
+            stop,
+            finished: false,
+        }
+    }
+
+    /// all paths found since the last call
+    pub fn take_new(&mut self) -> Vec<String> {
+        let mut entries = Vec::new();
+        loop {
+            match self.receiver.try_recv() {
+                Ok(batch) => entries.extend(batch),
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    self.finished = true;
+                    break;
+                }
+            }
+        }
+        entries
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+impl Drop for TreeWalker {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
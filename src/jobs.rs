@@ -0,0 +1,380 @@
+//! Background jobs for long running file operations.
+//!
+//! Every job runs on its own worker thread and reports progress, errors and the journal actions
+//! of the finished operation back to the UI thread over a channel. Pausing and cancelling is
+//! requested through a shared atomic the worker checks between (and during) single files.
+
+use crate::journal::Action;
+use std::fs;
+use std::io::{self, Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+const RUNNING: u8 = 0;
+const PAUSED: u8 = 1;
+const CANCELLED: u8 = 2;
+
+/// minimum time between two progress reports of a worker
+const REPORT_INTERVAL: Duration = Duration::from_millis(100);
+/// how long finished jobs stay visible in the jobs panel
+const FINISHED_JOB_LINGER: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Copy, Default)]
+pub struct Progress {
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    pub files_done: u64,
+    pub files_total: u64,
+}
+
+enum JobEvent {
+    Progress(Progress),
+    Error(io::Error),
+    Finished(String, Vec<Action>),
+}
+
+/// Handed to the work function of a job, reports progress and answers pause/cancel requests.
+/// A detached context (used for synchronous operations like undo) just collects the errors.
+pub struct JobContext {
+    control: Option<Arc<AtomicU8>>,
+    sender: Option<Sender<JobEvent>>,
+    progress: Progress,
+    last_report: Instant,
+    errors: Vec<io::Error>,
+    error_count: usize,
+}
+
+impl JobContext {
+    pub fn detached() -> JobContext {
+        JobContext {
+            control: None,
+            sender: None,
+            progress: Progress::default(),
+            last_report: Instant::now(),
+            errors: Vec::new(),
+            error_count: 0,
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.control
+            .as_ref()
+            .is_some_and(|control| control.load(Ordering::Relaxed) == CANCELLED)
+    }
+
+    /// blocks while the job is paused, returns an Interrupted error if it got cancelled
+    pub fn checkpoint(&mut self) -> io::Result<()> {
+        let Some(control) = &self.control else {
+            return Ok(());
+        };
+        loop {
+            match control.load(Ordering::Relaxed) {
+                PAUSED => thread::sleep(Duration::from_millis(50)),
+                CANCELLED => return Err(Error::new(ErrorKind::Interrupted, "Job cancelled")),
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    /// report an error, the operation continues with the next entry
+    pub fn error(&mut self, error: io::Error) {
+        //the cancellation itself is not an error worth reporting
+        if error.kind() == ErrorKind::Interrupted && self.is_cancelled() {
+            return;
+        }
+        self.error_count += 1;
+        match &self.sender {
+            Some(sender) => {
+                let _ = sender.send(JobEvent::Error(error));
+            }
+            None => self.errors.push(error),
+        }
+    }
+
+    /// number of errors reported so far
+    pub fn error_count(&self) -> usize {
+        self.error_count
+    }
+
+    pub fn take_errors(&mut self) -> Vec<io::Error> {
+        std::mem::take(&mut self.errors)
+    }
+
+    /// add the size of `paths` (recursively, without following symlinks) to the totals
+    pub fn add_to_totals(&mut self, path: &Path) {
+        let mut stack = vec![path.to_path_buf()];
+        while let Some(path) = stack.pop() {
+            let Ok(meta) = path.symlink_metadata() else {
+                continue;
+            };
+            self.progress.files_total += 1;
+            if meta.is_file() {
+                self.progress.bytes_total += meta.len();
+            } else if meta.is_dir()
+                && let Ok(iter) = fs::read_dir(&path)
+            {
+                stack.extend(iter.flatten().map(|entry| entry.path()));
+            }
+        }
+        self.report(true);
+    }
+
+    /// count an entry that is handled as a whole, e.g. a renamed directory
+    pub fn add_single_to_totals(&mut self) {
+        self.progress.files_total += 1;
+    }
+
+    pub fn bytes_done(&mut self, bytes: u64) {
+        self.progress.bytes_done += bytes;
+        self.report(false);
+    }
+
+    pub fn file_done(&mut self) {
+        self.progress.files_done += 1;
+        self.report(false);
+    }
+
+    fn report(&mut self, force: bool) {
+        if let Some(sender) = &self.sender
+            && (force || self.last_report.elapsed() >= REPORT_INTERVAL)
+        {
+            self.last_report = Instant::now();
+            let _ = sender.send(JobEvent::Progress(self.progress));
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum JobStatus {
+    Running,
+    Paused,
+    Cancelling,
+    Finished,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            JobStatus::Running => "running",
+            JobStatus::Paused => "paused",
+            JobStatus::Cancelling => "cancelling",
+            JobStatus::Finished => "done",
+            JobStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+pub struct Job {
+    pub description: String,
+    pub progress: Progress,
+    pub status: JobStatus,
+    control: Arc<AtomicU8>,
+    receiver: Receiver<JobEvent>,
+    handle: Option<JoinHandle<()>>,
+    //the paths the job changes, handed back with the result
+    changed: Vec<PathBuf>,
+    //time spent running, without the time the job was paused
+    active_time: Duration,
+    running_since: Option<Instant>,
+    finished_at: Option<Instant>,
+}
+
+impl Job {
+    fn elapsed(&self) -> Duration {
+        self.active_time
+            + self
+                .running_since
+                .map(|since| since.elapsed())
+                .unwrap_or_default()
+    }
+
+    /// bytes per second while the job was running
+    pub fn throughput(&self) -> f64 {
+        let secs = self.elapsed().as_secs_f64();
+        if secs > 0.0 {
+            self.progress.bytes_done as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    /// estimated remaining time, None until there is something to estimate from
+    pub fn eta(&self) -> Option<Duration> {
+        let throughput = self.throughput();
+        if throughput <= 0.0 || self.progress.bytes_total == 0 {
+            return None;
+        }
+        let remaining = self
+            .progress
+            .bytes_total
+            .saturating_sub(self.progress.bytes_done);
+        Some(Duration::from_secs_f64(remaining as f64 / throughput))
+    }
+
+    /// finished fraction, by bytes if there are any, otherwise by files
+    pub fn ratio(&self) -> f64 {
+        let progress = &self.progress;
+        let ratio = if progress.bytes_total > 0 {
+            progress.bytes_done as f64 / progress.bytes_total as f64
+        } else if progress.files_total > 0 {
+            progress.files_done as f64 / progress.files_total as f64
+        } else {
+            0.0
+        };
+        ratio.clamp(0.0, 1.0)
+    }
+
+    pub fn is_active(&self) -> bool {
+        matches!(
+            self.status,
+            JobStatus::Running | JobStatus::Paused | JobStatus::Cancelling
+        )
+    }
+
+    fn stop_clock(&mut self) {
+        if let Some(since) = self.running_since.take() {
+            self.active_time += since.elapsed();
+        }
+    }
+}
+
+/// the result of a job that finished since the last poll
+pub struct FinishedJob {
+    pub description: String,
+    pub actions: Vec<Action>,
+    /// the paths the job was started with, e.g. the pasted files and the target directory
+    pub changed: Vec<PathBuf>,
+}
+
+pub struct JobManager {
+    jobs: Vec<Job>,
+}
+
+impl JobManager {
+    pub fn new() -> JobManager {
+        JobManager { jobs: Vec::new() }
+    }
+
+    /// run `work` on a worker thread. It returns the description and the journal actions of the
+    /// operation, both are handed back through poll once the job finished together with the
+    /// `changed` paths.
+    pub fn spawn<F>(&mut self, description: String, changed: Vec<PathBuf>, work: F)
+    where
+        F: FnOnce(&mut JobContext) -> (String, Vec<Action>) + Send + 'static,
+    {
+        let control = Arc::new(AtomicU8::new(RUNNING));
+        let (sender, receiver) = mpsc::channel();
+
+        let mut context = JobContext {
+            control: Some(control.clone()),
+            sender: Some(sender.clone()),
+            progress: Progress::default(),
+            last_report: Instant::now(),
+            errors: Vec::new(),
+            error_count: 0,
+        };
+        let handle = thread::spawn(move || {
+            let (description, actions) = work(&mut context);
+            context.report(true);
+            let _ = sender.send(JobEvent::Finished(description, actions));
+        });
+
+        self.jobs.push(Job {
+            description,
+            progress: Progress::default(),
+            status: JobStatus::Running,
+            control,
+            receiver,
+            handle: Some(handle),
+            changed,
+            active_time: Duration::ZERO,
+            running_since: Some(Instant::now()),
+            finished_at: None,
+        });
+    }
+
+    /// process the events of all workers, returns the errors and the finished jobs
+    pub fn poll(&mut self) -> (Vec<io::Error>, Vec<FinishedJob>) {
+        let mut errors = Vec::new();
+        let mut finished = Vec::new();
+        for job in &mut self.jobs {
+            while let Ok(event) = job.receiver.try_recv() {
+                match event {
+                    JobEvent::Progress(progress) => job.progress = progress,
+                    JobEvent::Error(e) => errors.push(e),
+                    JobEvent::Finished(description, actions) => {
+                        job.stop_clock();
+                        job.status = if job.status == JobStatus::Cancelling {
+                            JobStatus::Cancelled
+                        } else {
+                            JobStatus::Finished
+                        };
+                        job.finished_at = Some(Instant::now());
+                        if let Some(handle) = job.handle.take() {
+                            let _ = handle.join();
+                        }
+                        finished.push(FinishedJob {
+                            description,
+                            actions,
+                            changed: std::mem::take(&mut job.changed),
+                        });
+                    }
+                }
+            }
+        }
+        self.jobs.retain(|job| {
+            job.finished_at
+                .is_none_or(|finished_at| finished_at.elapsed() < FINISHED_JOB_LINGER)
+        });
+        (errors, finished)
+    }
+
+    pub fn jobs(&self) -> &[Job] {
+        &self.jobs
+    }
+
+    pub fn toggle_pause(&mut self, index: usize) {
+        let Some(job) = self.jobs.get_mut(index) else {
+            return;
+        };
+        match job.status {
+            JobStatus::Running => {
+                job.control.store(PAUSED, Ordering::Relaxed);
+                job.status = JobStatus::Paused;
+                job.stop_clock();
+            }
+            JobStatus::Paused => {
+                job.control.store(RUNNING, Ordering::Relaxed);
+                job.status = JobStatus::Running;
+                job.running_since = Some(Instant::now());
+            }
+            _ => {}
+        }
+    }
+
+    pub fn cancel(&mut self, index: usize) {
+        if let Some(job) = self.jobs.get_mut(index)
+            && matches!(job.status, JobStatus::Running | JobStatus::Paused)
+        {
+            job.control.store(CANCELLED, Ordering::Relaxed);
+            job.status = JobStatus::Cancelling;
+        }
+    }
+
+    /// cancel all jobs and wait for the workers to stop
+    pub fn cancel_all(&mut self) {
+        for index in 0..self.jobs.len() {
+            self.cancel(index);
+        }
+        for job in &mut self.jobs {
+            if let Some(handle) = job.handle.take() {
+                let _ = handle.join();
+            }
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use crate::file_operations;
+use crate::jobs::JobContext;
+use crate::trash::{self, Trash, TrashEntry};
+use std::collections::VecDeque;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const JOURNAL_CAPACITY: usize = 100;
+
+/// A single change to the filesystem that can be reverted.
+pub enum Action {
+    Created(PathBuf),
+    Trashed(TrashEntry),
+    Moved { from: PathBuf, to: PathBuf },
+}
+
+impl Action {
+    /// the paths the action changed
+    pub fn paths(&self) -> Vec<&Path> {
+        match self {
+            Action::Created(path) => vec![path],
+            Action::Trashed(entry) => vec![&entry.original],
+            Action::Moved { from, to } => vec![from, to],
+        }
+    }
+
+    /// revert the action and return the action that reverts the revert
+    fn revert(&self, trash: &Trash, ctx: &mut JobContext) -> io::Result<Action> {
+        match self {
+            Action::Created(path) => Ok(Action::Trashed(trash.move_to_trash(path)?)),
+            Action::Trashed(entry) => {
+                trash::restore(entry)?;
+                Ok(Action::Created(entry.original.clone()))
+            }
+            Action::Moved { from, to } => {
+                file_operations::move_path(to, from, ctx)?;
+                Ok(Action::Moved {
+                    from: to.clone(),
+                    to: from.clone(),
+                })
+            }
+        }
+    }
+}
+
+/// All actions of one user operation, e.g. one paste of the whole selection.
+pub struct JournalEntry {
+    pub description: String,
+    actions: Vec<Action>,
+}
+
+impl JournalEntry {
+    /// the paths all actions of the operation changed
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.actions.iter().flat_map(Action::paths)
+    }
+
+    /// revert all actions in reverse order, the returned entry reverts this revert again.
+    /// Its actions are kept in execution order, so order dependent moves (e.g. swapping two
+    /// names through a temporary name) are reverted correctly as well.
+    fn revert(self, trash: &Trash, ctx: &mut JobContext) -> JournalEntry {
+        let mut reverted = Vec::with_capacity(self.actions.len());
+        for action in self.actions.iter().rev() {
+            match action.revert(trash, ctx) {
+                Ok(inverse) => reverted.push(inverse),
+                Err(e) => ctx.error(e),
+            }
+        }
+        JournalEntry {
+            description: self.description,
+            actions: reverted,
+        }
+    }
+}
+
+/// Records the changes of all mutating FileManager operations to undo and redo them.
+pub struct Journal {
+    undo_stack: VecDeque<JournalEntry>,
+    redo_stack: Vec<JournalEntry>,
+}
+
+impl Journal {
+    pub fn new() -> Journal {
+        Journal {
+            undo_stack: VecDeque::new(),
+            redo_stack: Vec::new(),
+        }
+    }
+
+    ///add an operation to the journal, a new operation invalidates everything that was undone
+    pub fn record(&mut self, description: String, actions: Vec<Action>) {
+        if actions.is_empty() {
+            return;
+        }
+        self.redo_stack.clear();
+        if self.undo_stack.len() >= JOURNAL_CAPACITY {
+            self.undo_stack.pop_front();
+        }
+        self.undo_stack.push_back(JournalEntry {
+            description,
+            actions,
+        });
+    }
+
+    ///undo the last `count` operations, created files are moved into `trash`
+    pub fn undo(&mut self, count: usize, trash: &Trash, ctx: &mut JobContext) {
+        for _ in 0..count {
+            let Some(entry) = self.undo_stack.pop_back() else {
+                break;
+            };
+            let reverted = entry.revert(trash, ctx);
+            if !reverted.actions.is_empty() {
+                self.redo_stack.push(reverted);
+            }
+        }
+    }
+
+    ///redo the last `count` undone operations, created files are moved into `trash`
+    pub fn redo(&mut self, count: usize, trash: &Trash, ctx: &mut JobContext) {
+        for _ in 0..count {
+            let Some(entry) = self.redo_stack.pop() else {
+                break;
+            };
+            let reverted = entry.revert(trash, ctx);
+            if !reverted.actions.is_empty() {
+                self.undo_stack.push_back(reverted);
+            }
+        }
+    }
+
+    /// operations that can be undone, most recent first
+    pub fn undo_entries(&self) -> impl Iterator<Item = &JournalEntry> {
+        self.undo_stack.iter().rev()
+    }
+
+    /// undone operations that can be redone, the one the next redo replays first
+    pub fn redo_entries(&self) -> impl DoubleEndedIterator<Item = &JournalEntry> {
+        self.redo_stack.iter().rev()
+    }
+}
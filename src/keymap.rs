@@ -0,0 +1,823 @@
+//! The key bindings of the explorer. Every action has a name, the keys that trigger it can be
+//! changed in `$XDG_CONFIG_HOME/pathexplorer/keys.toml`, e.g.
+//!
+//! ```toml
+//! toggle_hidden = "."
+//! go_top = ["home", "g g"]
+//! ```
+//!
+//! A binding is a sequence of keys separated by spaces. A key is a character or the name of a
+//! special key like `enter` or `left`, with optional `ctrl-`, `alt-` and `shift-` prefixes.
+//! Digits are typed as a count in front of an action unless a binding starts with them.
+//!
+//! The popups and the trash and disk usage windows move with the keys of `up`, `down`, `go_top`
+//! and `go_bottom` and use `confirm` and `close`. Only single keys work there. Their own keys,
+//! e.g. `restore` in the trash, only have to differ from these and the other keys of the window.
+
+use crate::util;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Action {
+    Quit,
+    Help,
+    Down,
+    Up,
+    GoTop,
+    GoBottom,
+    EnterDir,
+    LeaveDir,
+    Pick,
+    CancelPick,
+    Open,
+    GoBack,
+    GoForward,
+    ChangePath,
+    FindFile,
+    RecentDirs,
+    SetMark,
+    JumpToMark,
+    Bookmarks,
+    OpenTab,
+    CloseTab,
+    NextTab,
+    PreviousTab,
+    Tab(usize),
+    DualPane,
+    SwitchPane,
+    MillerColumns,
+    TreeView,
+    TogglePreview,
+    Filter,
+    ClearFilter,
+    NextMatch,
+    PreviousMatch,
+    ToggleSelection,
+    ClearSelection,
+    Cut,
+    Paste,
+    Trash,
+    Delete,
+    Rename,
+    BulkRename,
+    NewFile,
+    Undo,
+    Redo,
+    History,
+    FocusJobs,
+    Sorting,
+    DirPositions,
+    Columns,
+    DirSizes,
+    ToggleHidden,
+    OpenTrash,
+    DiskUsage,
+    Confirm,
+    Close,
+    PauseJob,
+    CancelJob,
+    FilterMode,
+    RenameMark,
+    DeleteMark,
+    ForgetDir,
+    ToggleGitignore,
+    ToggleColumn,
+    MoveColumnUp,
+    MoveColumnDown,
+    ToggleUnits,
+    ApplyToAll,
+    Restore,
+    Purge,
+    Rescan,
+}
+
+/// where the keys of an action are used
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Scope {
+    Explorer,
+    /// every popup and window besides the explorer
+    Popups,
+    JobsPanel,
+    FilterInput,
+    Bookmarks,
+    RecentDirs,
+    FuzzyFinder,
+    Columns,
+    Conflict,
+    Trash,
+    DiskUsage,
+}
+
+/// every action with its name in keys.toml, its description and its default keys
+const ACTIONS: &[(Action, &str, &str, &[&str])] = &[
+    (Action::Quit, "quit", "quit file explorer", &["q"]),
+    (Action::Help, "help", "show key mappings", &["?"]),
+    (Action::Down, "down", "move down", &["j", "down", "ctrl-n"]),
+    (Action::Up, "up", "move up", &["k", "up", "ctrl-p"]),
+    (Action::GoTop, "go_top", "go to the first entry", &["home"]),
+    (
+        Action::GoBottom,
+        "go_bottom",
+        "go to the last entry",
+        &["end", "G"],
+    ),
+    (
+        Action::EnterDir,
+        "enter_dir",
+        "enter folder (expand in tree view)",
+        &["l", "right"],
+    ),
+    (
+        Action::LeaveDir,
+        "leave_dir",
+        "go to parent folder (collapse in tree view)",
+        &["h", "left"],
+    ),
+    (
+        Action::Pick,
+        "pick",
+        "pick highlighted entry or selection (--pick)",
+        &["enter"],
+    ),
+    (Action::Open, "open", "open file/folder", &["enter"]),
+    (Action::GoBack, "go_back", "go back", &["alt-left"]),
+    (
+        Action::GoForward,
+        "go_forward",
+        "go forward",
+        &["alt-right"],
+    ),
+    (Action::ChangePath, "change_path", "change path", &["e"]),
+    (
+        Action::FindFile,
+        "find_file",
+        "find file below current folder",
+        &["f"],
+    ),
+    (
+        Action::RecentDirs,
+        "recent_dirs",
+        "jump to a recent folder",
+        &["z"],
+    ),
+    (
+        Action::SetMark,
+        "set_mark",
+        "mark current folder (then a letter)",
+        &["m"],
+    ),
+    (
+        Action::JumpToMark,
+        "jump_to_mark",
+        "jump to mark (then a letter)",
+        &["'"],
+    ),
+    (Action::Bookmarks, "bookmarks", "list marks", &["M"]),
+    (Action::OpenTab, "open_tab", "open tab", &["ctrl-t"]),
+    (Action::CloseTab, "close_tab", "close tab", &["ctrl-w"]),
+    (Action::NextTab, "next_tab", "next tab", &[">"]),
+    (Action::PreviousTab, "previous_tab", "previous tab", &["<"]),
+    (Action::Tab(0), "tab_1", "show tab 1", &["alt-1"]),
+    (Action::Tab(1), "tab_2", "show tab 2", &["alt-2"]),
+    (Action::Tab(2), "tab_3", "show tab 3", &["alt-3"]),
+    (Action::Tab(3), "tab_4", "show tab 4", &["alt-4"]),
+    (Action::Tab(4), "tab_5", "show tab 5", &["alt-5"]),
+    (Action::Tab(5), "tab_6", "show tab 6", &["alt-6"]),
+    (Action::Tab(6), "tab_7", "show tab 7", &["alt-7"]),
+    (Action::Tab(7), "tab_8", "show tab 8", &["alt-8"]),
+    (Action::Tab(8), "tab_9", "show tab 9", &["alt-9"]),
+    (Action::DualPane, "dual_pane", "toggle dual pane", &["w"]),
+    (Action::SwitchPane, "switch_pane", "switch pane", &["tab"]),
+    (
+        Action::MillerColumns,
+        "miller_columns",
+        "toggle miller columns",
+        &["L"],
+    ),
+    (Action::TreeView, "tree_view", "toggle tree view", &["T"]),
+    (
+        Action::TogglePreview,
+        "toggle_preview",
+        "toggle preview",
+        &["p"],
+    ),
+    (Action::Filter, "filter", "filter folder", &["/"]),
+    (
+        Action::ClearFilter,
+        "clear_filter",
+        "clear filter",
+        &["esc"],
+    ),
+    (
+        Action::CancelPick,
+        "cancel_pick",
+        "cancel picking (--pick)",
+        &["esc"],
+    ),
+    (Action::NextMatch, "next_match", "next filter match", &["n"]),
+    (
+        Action::PreviousMatch,
+        "previous_match",
+        "previous filter match",
+        &["N"],
+    ),
+    (
+        Action::ToggleSelection,
+        "toggle_selection",
+        "select/unselect",
+        &["y"],
+    ),
+    (
+        Action::ClearSelection,
+        "clear_selection",
+        "clear selection",
+        &["c"],
+    ),
+    (
+        Action::Cut,
+        "cut",
+        "cut selection (paste moves)",
+        &["ctrl-x"],
+    ),
+    (
+        Action::Paste,
+        "paste",
+        "paste selection (into the other pane)",
+        &["v"],
+    ),
+    (Action::Trash, "trash", "move selection to trash", &["x"]),
+    (
+        Action::Delete,
+        "delete",
+        "delete selection permanently",
+        &["X"],
+    ),
+    (Action::Rename, "rename", "rename file/folder", &["r"]),
+    (
+        Action::BulkRename,
+        "bulk_rename",
+        "bulk rename selection in $EDITOR",
+        &["R"],
+    ),
+    (
+        Action::NewFile,
+        "new_file",
+        "create new file/folder",
+        &["n"],
+    ),
+    (
+        Action::Undo,
+        "undo",
+        "undo last operation (count repeats)",
+        &["u"],
+    ),
+    (
+        Action::Redo,
+        "redo",
+        "redo last operation (count repeats)",
+        &["ctrl-r"],
+    ),
+    (Action::History, "history", "show operation history", &["H"]),
+    (Action::FocusJobs, "focus_jobs", "focus jobs panel", &["J"]),
+    (Action::Sorting, "sorting", "open sorting popup", &["s"]),
+    (
+        Action::DirPositions,
+        "dir_positions",
+        "change folder positions",
+        &["d"],
+    ),
+    (Action::Columns, "columns", "choose table columns", &["C"]),
+    (
+        Action::DirSizes,
+        "dir_sizes",
+        "calculate folder sizes",
+        &["S"],
+    ),
+    (
+        Action::ToggleHidden,
+        "toggle_hidden",
+        "toggle hidden files",
+        &["g"],
+    ),
+    (Action::OpenTrash, "open_trash", "open trash", &["t"]),
+    (
+        Action::DiskUsage,
+        "disk_usage",
+        "analyze disk usage",
+        &["D"],
+    ),
+    (
+        Action::Confirm,
+        "confirm",
+        "choose the highlighted entry outside the explorer",
+        &["enter"],
+    ),
+    (
+        Action::Close,
+        "close",
+        "close a popup or window",
+        &["esc", "q"],
+    ),
+    (
+        Action::PauseJob,
+        "pause_job",
+        "pause/resume the highlighted job (jobs panel)",
+        &["p"],
+    ),
+    (
+        Action::CancelJob,
+        "cancel_job",
+        "cancel the highlighted job (jobs panel)",
+        &["c"],
+    ),
+    (
+        Action::FilterMode,
+        "filter_mode",
+        "switch the mode of the filter being typed",
+        &["tab"],
+    ),
+    (
+        Action::RenameMark,
+        "rename_mark",
+        "change the letter of the highlighted mark (list of marks)",
+        &["r"],
+    ),
+    (
+        Action::DeleteMark,
+        "delete_mark",
+        "delete the highlighted mark (list of marks)",
+        &["d", "x"],
+    ),
+    (
+        Action::ForgetDir,
+        "forget_dir",
+        "forget the highlighted recent folder",
+        &["ctrl-d"],
+    ),
+    (
+        Action::ToggleGitignore,
+        "toggle_gitignore",
+        "toggle .gitignore while finding files",
+        &["ctrl-g"],
+    ),
+    (
+        Action::ToggleColumn,
+        "toggle_column",
+        "show/hide the highlighted column",
+        &["space"],
+    ),
+    (
+        Action::MoveColumnUp,
+        "move_column_up",
+        "move the highlighted column up",
+        &["K"],
+    ),
+    (
+        Action::MoveColumnDown,
+        "move_column_down",
+        "move the highlighted column down",
+        &["J"],
+    ),
+    (
+        Action::ToggleUnits,
+        "toggle_units",
+        "switch between kB and KiB (columns popup)",
+        &["u"],
+    ),
+    (
+        Action::ApplyToAll,
+        "apply_to_all",
+        "use the choice for every conflict of a paste",
+        &["a", "space"],
+    ),
+    (
+        Action::Restore,
+        "restore",
+        "restore the highlighted file (trash)",
+        &["r"],
+    ),
+    (
+        Action::Purge,
+        "purge",
+        "delete the highlighted file permanently (trash)",
+        &["x"],
+    ),
+    (Action::Rescan, "rescan", "scan again (disk usage)", &["r"]),
+];
+
+impl Action {
+    /// actions that only do something in some situations, e.g. while a filter is set.
+    /// Their keys may be shared with another action that is used otherwise
+    pub fn is_conditional(&self) -> bool {
+        matches!(
+            self,
+            Action::Pick
+                | Action::CancelPick
+                | Action::ClearFilter
+                | Action::NextMatch
+                | Action::PreviousMatch
+                | Action::FocusJobs
+        )
+    }
+
+    /// actions that change files, they are refused in read-only mode
+    pub fn changes_files(&self) -> bool {
+        matches!(
+            self,
+            Action::Paste
+                | Action::Trash
+                | Action::Delete
+                | Action::Rename
+                | Action::BulkRename
+                | Action::NewFile
+                | Action::Undo
+                | Action::Redo
+        )
+    }
+
+    fn scope(&self) -> Scope {
+        match self {
+            Action::Confirm | Action::Close => Scope::Popups,
+            Action::PauseJob | Action::CancelJob => Scope::JobsPanel,
+            Action::FilterMode => Scope::FilterInput,
+            Action::RenameMark | Action::DeleteMark => Scope::Bookmarks,
+            Action::ForgetDir => Scope::RecentDirs,
+            Action::ToggleGitignore => Scope::FuzzyFinder,
+            Action::ToggleColumn
+            | Action::MoveColumnUp
+            | Action::MoveColumnDown
+            | Action::ToggleUnits => Scope::Columns,
+            Action::ApplyToAll => Scope::Conflict,
+            Action::Restore | Action::Purge => Scope::Trash,
+            Action::Rescan => Scope::DiskUsage,
+            _ => Scope::Explorer,
+        }
+    }
+
+    /// whether the action is used by the explorer
+    fn in_explorer(&self) -> bool {
+        self.scope() == Scope::Explorer
+    }
+
+    /// whether the action is used by the popups and the trash and disk usage windows.
+    /// Only single keys work there, typed characters are text in popups with a query
+    fn in_popups(&self) -> bool {
+        matches!(
+            self,
+            Action::Down
+                | Action::Up
+                | Action::GoTop
+                | Action::GoBottom
+                | Action::Confirm
+                | Action::Close
+        )
+    }
+
+    /// whether both actions are used in the same place, so their keys have to differ
+    fn shares_keys_with(&self, other: &Action) -> bool {
+        let own_keys = |action: &Action| !matches!(action.scope(), Scope::Explorer | Scope::Popups);
+        (self.in_explorer() && other.in_explorer())
+            || (self.in_popups() && other.in_popups())
+            || (own_keys(self) && (other.in_popups() || other.scope() == self.scope()))
+            || (own_keys(other) && self.in_popups())
+    }
+
+    fn name(&self) -> &'static str {
+        ACTIONS
+            .iter()
+            .find(|(action, ..)| action == self)
+            .map_or("", |(_, name, ..)| name)
+    }
+}
+
+/// a key with its modifiers, shift is part of the character for character keys
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+const KEY_NAMES: &[(&str, KeyCode)] = &[
+    ("enter", KeyCode::Enter),
+    ("esc", KeyCode::Esc),
+    ("tab", KeyCode::Tab),
+    ("backtab", KeyCode::BackTab),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("insert", KeyCode::Insert),
+    ("space", KeyCode::Char(' ')),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+];
+
+impl Key {
+    pub fn from_event(key_event: &KeyEvent) -> Key {
+        Key::new(key_event.code, key_event.modifiers)
+    }
+
+    fn new(code: KeyCode, mut modifiers: KeyModifiers) -> Key {
+        //terminals don't agree on whether an uppercase letter or backtab comes with shift
+        if let KeyCode::Char(_) | KeyCode::BackTab = code {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        modifiers &= KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT;
+        Key { code, modifiers }
+    }
+
+    /// parse a key like `x`, `ctrl-t` or `alt-left`
+    pub fn parse(text: &str) -> Option<Key> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text;
+        //a single character is a key even if it looks like a prefix, e.g. `-`
+        while rest.chars().count() > 1 {
+            if let Some(after) = rest.strip_prefix("ctrl-") {
+                modifiers |= KeyModifiers::CONTROL;
+                rest = after;
+            } else if let Some(after) = rest.strip_prefix("alt-") {
+                modifiers |= KeyModifiers::ALT;
+                rest = after;
+            } else if let Some(after) = rest.strip_prefix("shift-") {
+                modifiers |= KeyModifiers::SHIFT;
+                rest = after;
+            } else {
+                break;
+            }
+        }
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) if modifiers.contains(KeyModifiers::SHIFT) => {
+                KeyCode::Char(c.to_ascii_uppercase())
+            }
+            (Some(c), None) => KeyCode::Char(c),
+            _ => {
+                //names are not case sensitive, e.g. `F1` or `Enter`
+                let rest = rest.to_ascii_lowercase();
+                if let Some(number) = rest.strip_prefix('f')
+                    && let Ok(number) = number.parse()
+                {
+                    KeyCode::F(number)
+                } else {
+                    KEY_NAMES.iter().find(|(name, _)| *name == rest)?.1
+                }
+            }
+        };
+        Some(Key::new(code, modifiers))
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "alt-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "shift-")?;
+        }
+        match self.code {
+            KeyCode::F(number) => write!(f, "f{number}"),
+            KeyCode::Char(c) if c != ' ' => write!(f, "{c}"),
+            code => {
+                let name = KEY_NAMES.iter().find(|(_, c)| *c == code);
+                write!(f, "{}", name.map_or("?", |(name, _)| name))
+            }
+        }
+    }
+}
+
+/// parse a binding like `g g`, the keys are separated by spaces
+fn parse_sequence(text: &str) -> Option<Vec<Key>> {
+    let keys: Option<Vec<Key>> = text.split_whitespace().map(Key::parse).collect();
+    keys.filter(|keys| !keys.is_empty())
+}
+
+fn format_sequence(keys: &[Key]) -> String {
+    let keys: Vec<String> = keys.iter().map(Key::to_string).collect();
+    format!("<{}>", keys.join(" "))
+}
+
+/// what the keys pressed so far mean
+pub enum Lookup {
+    /// the actions bound to the keys, try them in order until one applies
+    Actions(Vec<Action>),
+    /// the keys are the beginning of a longer binding
+    Pending,
+    None,
+}
+
+pub struct Keymap {
+    //in the order of ACTIONS, so earlier actions win conflicts
+    bindings: Vec<(Vec<Key>, Action)>,
+}
+
+impl Keymap {
+    /// the keymap without a keys.toml
+    pub fn default_keys() -> Keymap {
+        let bindings = ACTIONS
+            .iter()
+            .flat_map(|(action, _, _, keys)| {
+                keys.iter()
+                    .filter_map(|keys| Some((parse_sequence(keys)?, *action)))
+            })
+            .collect();
+        Keymap { bindings }
+    }
+
+    /// load the keys from the config directory, the defaults are used without one.
+    /// Returns the problems of the file and the conflicting bindings
+    pub fn load_default() -> (Keymap, Vec<io::Error>) {
+        match util::xdg_dir("XDG_CONFIG_HOME", ".config") {
+            Some(config) => Keymap::load(config.join("pathexplorer").join("keys.toml")),
+            None => (Keymap::default_keys(), Vec::new()),
+        }
+    }
+
+    /// the defaults with the bindings of `file` in place of the ones of the actions it names
+    pub fn load(file: PathBuf) -> (Keymap, Vec<io::Error>) {
+        let mut keymap = Keymap::default_keys();
+        let content = match fs::read_to_string(&file) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return (keymap, Vec::new()),
+            Err(e) => return (keymap, vec![e]),
+        };
+        let mut errors = match keymap.apply(&content) {
+            Ok(errors) => errors,
+            Err(e) => vec![e],
+        };
+        errors.extend(keymap.conflicts());
+        for e in &mut errors {
+            *e = invalid(format!("{}: {e}", file.display()));
+        }
+        (keymap, errors)
+    }
+
+    /// replace the bindings of the actions in the toml `content`. A wrong binding is reported
+    /// and skipped, the rest still applies
+    fn apply(&mut self, content: &str) -> io::Result<Vec<io::Error>> {
+        let table: toml::Table = content
+            .parse()
+            .map_err(|e: toml::de::Error| invalid(e.message().to_owned()))?;
+        let mut errors = Vec::new();
+        for (name, value) in table {
+            let Some((action, ..)) = ACTIONS.iter().find(|(_, n, ..)| *n == name) else {
+                errors.push(invalid(format!("unknown action {name}")));
+                continue;
+            };
+            let texts: Vec<&str> = match &value {
+                toml::Value::String(text) => vec![text.as_str()],
+                toml::Value::Array(values) => values.iter().filter_map(|v| v.as_str()).collect(),
+                _ => Vec::new(),
+            };
+            if texts.is_empty()
+                && !matches!(&value, toml::Value::Array(values) if values.is_empty())
+            {
+                errors.push(invalid(format!("{name} needs a key or a list of keys")));
+                continue;
+            }
+            self.bindings.retain(|(_, bound)| bound != action);
+            for text in texts {
+                match parse_sequence(text) {
+                    Some(keys) => self.bindings.push((keys, *action)),
+                    None => errors.push(invalid(format!("{name}: unknown key {text:?}"))),
+                }
+            }
+        }
+        let order = |action: &Action| ACTIONS.iter().position(|(a, ..)| a == action);
+        self.bindings.sort_by_key(|(_, action)| order(action));
+        Ok(errors)
+    }
+
+    /// bindings that can't be used: keys bound twice, and longer bindings of the explorer that
+    /// start with the keys of a shorter one
+    pub fn conflicts(&self) -> Vec<io::Error> {
+        let mut conflicts = Vec::new();
+        for (index, (keys, action)) in self.bindings.iter().enumerate() {
+            if !action.in_explorer() && keys.len() > 1 {
+                conflicts.push(invalid(format!(
+                    "{} of {} can't be used, only single keys work outside the explorer",
+                    format_sequence(keys),
+                    action.name()
+                )));
+            }
+            for (other_keys, other) in &self.bindings[..index] {
+                if !action.shares_keys_with(other) {
+                    continue;
+                }
+                if other_keys == keys && !other.is_conditional() {
+                    conflicts.push(invalid(format!(
+                        "{} is bound to {} and {}, only {} is used",
+                        format_sequence(keys),
+                        other.name(),
+                        action.name(),
+                        other.name()
+                    )));
+                } else if action.in_explorer()
+                    && other.in_explorer()
+                    && other_keys != keys
+                    && (keys.starts_with(other_keys) || other_keys.starts_with(keys))
+                {
+                    let (short, long) = if keys.len() < other_keys.len() {
+                        ((keys, action), (other_keys, other))
+                    } else {
+                        ((other_keys, other), (keys, action))
+                    };
+                    conflicts.push(invalid(format!(
+                        "{} of {} can't be used, it starts with {} of {}",
+                        format_sequence(long.0),
+                        long.1.name(),
+                        format_sequence(short.0),
+                        short.1.name()
+                    )));
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// the meaning of the keys pressed so far in the explorer
+    pub fn lookup(&self, keys: &[Key]) -> Lookup {
+        let bindings = || {
+            self.bindings
+                .iter()
+                .filter(|(_, action)| action.in_explorer())
+        };
+        let actions: Vec<Action> = bindings()
+            .filter(|(bound, _)| bound == keys)
+            .map(|(_, action)| *action)
+            .collect();
+        if !actions.is_empty() {
+            Lookup::Actions(actions)
+        } else if bindings().any(|(bound, _)| bound.starts_with(keys)) {
+            Lookup::Pending
+        } else {
+            Lookup::None
+        }
+    }
+
+    /// the action of a key in the popups and the trash and disk usage windows, see
+    /// `Action::in_popups`
+    pub fn popup_action(&self, key_event: &KeyEvent) -> Option<Action> {
+        let key = Key::from_event(key_event);
+        self.bindings
+            .iter()
+            .find(|(bound, action)| action.in_popups() && *bound == [key])
+            .map(|(_, action)| *action)
+    }
+
+    /// whether the single key of `key_event` is bound to `action`, e.g. to close a window
+    /// with the key that opened it
+    pub fn is_bound(&self, key_event: &KeyEvent, action: Action) -> bool {
+        let key = Key::from_event(key_event);
+        self.bindings
+            .iter()
+            .any(|(bound, bound_action)| *bound_action == action && *bound == [key])
+    }
+
+    /// the first single key of `action` for the short help of a window, e.g. `<q>`
+    pub fn hint(&self, action: Action) -> String {
+        self.bindings
+            .iter()
+            .find(|(keys, bound)| *bound == action && keys.len() == 1)
+            .map_or_else(|| String::from("<->"), |(keys, _)| format_sequence(keys))
+    }
+
+    /// one line per action with its keys, e.g. `<j>/<down> → move down`
+    pub fn help_lines(&self) -> Vec<String> {
+        ACTIONS
+            .iter()
+            .filter_map(|(action, _, description, _)| {
+                let keys: Vec<String> = self
+                    .bindings
+                    .iter()
+                    .filter(|(_, bound)| bound == action)
+                    .map(|(keys, _)| format_sequence(keys))
+                    .collect();
+                (!keys.is_empty()).then(|| format!("{} → {description}", keys.join("/")))
+            })
+            .collect()
+    }
+}
+
+/// whether the key types a character into the query of a popup
+pub fn is_text(key_event: &KeyEvent) -> bool {
+    matches!(key_event.code, KeyCode::Char(_))
+        && !key_event
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
@@ -0,0 +1,48 @@
+//! Listings of the directories around the current one, shown by the miller columns.
+
+use std::fs::DirEntry;
+use std::path::{Path, PathBuf};
+
+/// at most this many listings are kept, the least recently used is dropped first
+const MAX_LISTINGS: usize = 16;
+
+pub struct ListingCache {
+    //the most recently used listing is at the end
+    listings: Vec<(PathBuf, Vec<DirEntry>)>,
+}
+
+impl ListingCache {
+    pub fn new() -> ListingCache {
+        ListingCache {
+            listings: Vec::new(),
+        }
+    }
+
+    /// list `dir` with `read` unless it is cached, it becomes the most recently used listing
+    pub fn load(&mut self, dir: &Path, read: impl FnOnce() -> Vec<DirEntry>) {
+        match self.listings.iter().position(|(path, _)| path == dir) {
+            Some(index) => {
+                let listing = self.listings.remove(index);
+                self.listings.push(listing);
+            }
+            None => {
+                if self.listings.len() >= MAX_LISTINGS {
+                    self.listings.remove(0);
+                }
+                self.listings.push((dir.to_path_buf(), read()));
+            }
+        }
+    }
+
+    pub fn get(&self, dir: &Path) -> Option<&[DirEntry]> {
+        self.listings
+            .iter()
+            .find(|(path, _)| path == dir)
+            .map(|(_, entries)| entries.as_slice())
+    }
+
+    /// forget all listings, e.g. after files were changed
+    pub fn clear(&mut self) {
+        self.listings.clear();
+    }
+}
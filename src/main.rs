@@ -1,18 +1,105 @@
 mod app;
+mod bookmarks;
+mod cli;
+mod columns;
+mod config;
 mod controller;
+mod dir_size;
+mod disk_usage;
 mod file_manager;
+mod file_operations;
+mod filter;
+mod fuzzy;
+mod jobs;
+mod journal;
+mod keymap;
+mod listing_cache;
 mod message;
+mod navigation;
+mod preview;
+mod shell_init;
 mod string_ring_buffer;
 mod test;
+mod trash;
+mod tui;
 mod util;
+mod watcher;
 mod windows;
 
 use crate::app::App;
-use std::io;
+use crate::cli::{Command, Options};
+use crate::tui::Output;
+use std::io::{self, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
-fn main() -> io::Result<()> {
-    let mut terminal = ratatui::init();
-    let res: io::Result<()> = App::new().run(&mut terminal);
-    ratatui::restore();
-    res
+fn main() -> io::Result<ExitCode> {
+    let options = match cli::parse(std::env::args_os().skip(1)) {
+        Ok(Command::Run(options)) => options,
+        Ok(Command::Help) => {
+            print!("{}", cli::USAGE);
+            return Ok(ExitCode::SUCCESS);
+        }
+        Ok(Command::Version) => {
+            println!("pathexplorer {}", env!("CARGO_PKG_VERSION"));
+            return Ok(ExitCode::SUCCESS);
+        }
+        Ok(Command::ShellInit(script)) => {
+            print!("{script}");
+            return Ok(ExitCode::SUCCESS);
+        }
+        //report problems before the terminal is taken over, they would be lost otherwise
+        Err(e) => {
+            eprintln!("pathexplorer: {e}\nTry 'pathexplorer --help' for more information.");
+            return Ok(ExitCode::from(2));
+        }
+    };
+    //stdout is kept free for the printed directory or the picked paths
+    let output = if options.print_cwd || (options.pick.is_some() && options.output.is_none()) {
+        Output::Stderr
+    } else {
+        Output::Stdout
+    };
+    let mut terminal = tui::init(output)?;
+    let mut app = App::new(&options);
+    let res: io::Result<()> = app.run(&mut terminal);
+    tui::restore()?;
+    res?;
+    write_last_dir(&options, &app.current_dir())?;
+    if options.pick.is_some() {
+        //like fzf, scripts can tell a cancelled pick from an error
+        if app.picked().is_empty() {
+            return Ok(ExitCode::from(130));
+        }
+        write_picked(&options, app.picked())?;
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// every path is followed by a newline, or by NUL with `--null`
+fn write_picked(options: &Options, paths: &[PathBuf]) -> io::Result<()> {
+    let separator = if options.null { b'\0' } else { b'\n' };
+    let mut content = Vec::new();
+    for path in paths {
+        content.extend_from_slice(path.as_os_str().as_bytes());
+        content.push(separator);
+    }
+    match &options.output {
+        Some(file) => std::fs::write(file, content),
+        None => io::stdout().write_all(&content),
+    }
+}
+
+/// tell the shell where the explorer was quit, see shell_init
+fn write_last_dir(options: &Options, dir: &Path) -> io::Result<()> {
+    if let Some(file) = &options.choosedir {
+        std::fs::write(file, dir.as_os_str().as_bytes())?;
+    }
+    if options.print_cwd {
+        let mut stdout = io::stdout();
+        stdout.write_all(dir.as_os_str().as_bytes())?;
+        stdout.write_all(b"\n")?;
+    }
+    Ok(())
 }
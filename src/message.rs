@@ -1,7 +1,16 @@
+use crate::file_operations::ConflictPolicy;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
 pub enum Message {
     String(String),
     Bool(bool),
     TwoStrings(String, String),
+    StringList(Vec<String>),
+    Paths(Vec<PathBuf>),
+    //the directory that is pasted into and the sources whose names exist there
+    PasteConflicts(PathBuf, Vec<PathBuf>),
+    ConflictResolutions(HashMap<PathBuf, ConflictPolicy>),
 }
 
 pub trait MessageSender {
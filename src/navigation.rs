@@ -0,0 +1,218 @@
+//! Where the user has been: a back/forward stack for the session and a list of recently
+//! visited directories that is kept across sessions and ranked by frecency.
+
+use crate::util;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// at most this many directories are remembered in each direction
+const MAX_HISTORY: usize = 100;
+/// once the ranks add up to more than this, all of them are aged
+const MAX_TOTAL_RANK: f64 = 1000.0;
+/// aged directories with a lower rank are forgotten
+const MIN_RANK: f64 = 1.0;
+
+const HOUR: i64 = 60 * 60;
+const DAY: i64 = 24 * HOUR;
+const WEEK: i64 = 7 * DAY;
+
+/// Back/forward stacks like the ones of a browser
+pub struct History {
+    back: Vec<PathBuf>,
+    forward: Vec<PathBuf>,
+}
+
+impl History {
+    pub fn new() -> History {
+        History {
+            back: Vec::new(),
+            forward: Vec::new(),
+        }
+    }
+
+    /// `from` was left for a new directory, the forward stack is dropped
+    pub fn visit(&mut self, from: PathBuf) {
+        push_capped(&mut self.back, from);
+        self.forward.clear();
+    }
+
+    /// the previous directory, `current` can be reached again with forward
+    pub fn back(&mut self, current: PathBuf) -> Option<PathBuf> {
+        let target = self.back.pop()?;
+        push_capped(&mut self.forward, current);
+        Some(target)
+    }
+
+    /// the directory that was left with back, `current` can be reached again with back
+    pub fn forward(&mut self, current: PathBuf) -> Option<PathBuf> {
+        let target = self.forward.pop()?;
+        push_capped(&mut self.back, current);
+        Some(target)
+    }
+
+    /// undo the last back (or forward) because its target could not be entered.
+    /// The target is forgotten.
+    pub fn revert(&mut self, was_back: bool) {
+        if was_back {
+            self.forward.pop();
+        } else {
+            self.back.pop();
+        }
+    }
+}
+
+fn push_capped(stack: &mut Vec<PathBuf>, path: PathBuf) {
+    if stack.len() >= MAX_HISTORY {
+        stack.remove(0);
+    }
+    stack.push(path);
+}
+
+pub struct RecentDir {
+    pub path: PathBuf,
+    rank: f64,
+    last_visit: i64,
+}
+
+impl RecentDir {
+    /// the rank weighted by how long ago the directory was visited
+    pub fn frecency(&self, now: i64) -> f64 {
+        let age = now - self.last_visit;
+        let weight = if age < HOUR {
+            4.0
+        } else if age < DAY {
+            2.0
+        } else if age < WEEK {
+            0.5
+        } else {
+            0.25
+        };
+        self.rank * weight
+    }
+}
+
+/// Recently visited directories, saved in `$XDG_STATE_HOME/pathexplorer/recent_dirs`
+pub struct RecentDirs {
+    file: Option<PathBuf>,
+    dirs: Vec<RecentDir>,
+}
+
+impl RecentDirs {
+    /// load the list from the state directory, nothing is saved if there is none
+    pub fn load_default() -> (RecentDirs, Option<io::Error>) {
+        let file = util::xdg_dir("XDG_STATE_HOME", ".local/state")
+            .map(|state| state.join("pathexplorer").join("recent_dirs"));
+        match file {
+            Some(file) => RecentDirs::load(file),
+            None => (
+                RecentDirs {
+                    file: None,
+                    dirs: Vec::new(),
+                },
+                None,
+            ),
+        }
+    }
+
+    /// load the list from `file`, a missing file is an empty list
+    pub fn load(file: PathBuf) -> (RecentDirs, Option<io::Error>) {
+        let content = fs::read_to_string(&file);
+        let mut recent = RecentDirs {
+            file: Some(file),
+            dirs: Vec::new(),
+        };
+        let content = match content {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return (recent, None),
+            Err(e) => return (recent, Some(e)),
+        };
+        //one directory per line: rank, time of the last visit and the path, separated by tabs
+        for line in content.lines() {
+            let mut fields = line.splitn(3, '\t');
+            let (Some(rank), Some(last_visit), Some(path)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+            if let (Ok(rank), Ok(last_visit)) = (rank.parse(), last_visit.parse()) {
+                recent.dirs.push(RecentDir {
+                    path: PathBuf::from(path),
+                    rank,
+                    last_visit,
+                });
+            }
+        }
+        (recent, None)
+    }
+
+    /// count a visit of `path` and save the list
+    pub fn visit(&mut self, path: &Path) -> io::Result<()> {
+        let now = util::now_secs();
+        match self.dirs.iter_mut().find(|dir| dir.path == path) {
+            Some(dir) => {
+                dir.rank += 1.0;
+                dir.last_visit = now;
+            }
+            None => self.dirs.push(RecentDir {
+                path: path.to_path_buf(),
+                rank: 1.0,
+                last_visit: now,
+            }),
+        }
+        let total: f64 = self.dirs.iter().map(|dir| dir.rank).sum();
+        if total > MAX_TOTAL_RANK {
+            for dir in &mut self.dirs {
+                dir.rank *= 0.9;
+            }
+            self.dirs.retain(|dir| dir.rank >= MIN_RANK);
+        }
+        self.save()
+    }
+
+    /// forget a directory, e.g. because it does not exist anymore
+    pub fn remove(&mut self, path: &Path) -> io::Result<()> {
+        self.dirs.retain(|dir| dir.path != path);
+        self.save()
+    }
+
+    /// the directories with the highest frecency first
+    pub fn ranked(&self) -> Vec<&RecentDir> {
+        let now = util::now_secs();
+        let mut dirs: Vec<&RecentDir> = self.dirs.iter().collect();
+        dirs.sort_by(|a, b| b.frecency(now).total_cmp(&a.frecency(now)));
+        dirs
+    }
+
+    /// saving stops after the first error, so it is only reported once
+    fn save(&mut self) -> io::Result<()> {
+        let result = self.write();
+        if result.is_err() {
+            self.file = None;
+        }
+        result
+    }
+
+    fn write(&self) -> io::Result<()> {
+        let Some(file) = &self.file else {
+            return Ok(());
+        };
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content: String = self
+            .dirs
+            .iter()
+            .filter_map(|dir| {
+                let path = dir.path.to_str()?;
+                //a newline would break the format, such paths are not remembered
+                (!path.contains('\n'))
+                    .then(|| format!("{}\t{}\t{}\n", dir.rank, dir.last_visit, path))
+            })
+            .collect();
+        //write a temporary file first so a crash never leaves a half written list
+        let temp = file.with_extension("tmp");
+        fs::write(&temp, content)?;
+        fs::rename(temp, file)
+    }
+}
@@ -0,0 +1,274 @@
+//! Preview of the highlighted entry. Previews are built on a worker thread, only the latest
+//! request gets loaded, so scrolling through the table never waits for a file to be read.
+
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::parsing::SyntaxSet;
+
+/// at most this many bytes of a file are read for the preview
+const MAX_PREVIEW_BYTES: u64 = 64 * 1024;
+/// at most this many lines of text or children of a directory are shown
+const MAX_PREVIEW_LINES: usize = 200;
+/// binary files show a hex dump of this many bytes
+const MAX_HEX_BYTES: usize = 4096;
+const HEX_BYTES_PER_LINE: usize = 16;
+const THEME: &str = "base16-ocean.dark";
+
+pub struct Preview {
+    pub path: PathBuf,
+    pub lines: Vec<Line<'static>>,
+    /// only the beginning of the content is shown
+    pub truncated: bool,
+}
+
+/// Loads previews on a worker thread that lives as long as the previewer.
+pub struct Previewer {
+    requests: Sender<PathBuf>,
+    results: Receiver<Preview>,
+    requested: Option<PathBuf>,
+    current: Option<Preview>,
+}
+
+impl Previewer {
+    pub fn new() -> Previewer {
+        let (request_sender, request_receiver) = mpsc::channel::<PathBuf>();
+        let (result_sender, result_receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            let mut highlighter = Highlighter::new();
+            while let Ok(mut path) = request_receiver.recv() {
+                //skip everything that was requested while the last preview was loading
+                while let Ok(newer) = request_receiver.try_recv() {
+                    path = newer;
+                }
+                let preview = load(&path, &mut highlighter);
+                if result_sender.send(preview).is_err() {
+                    return;
+                }
+            }
+        });
+
+        Previewer {
+            requests: request_sender,
+            results: result_receiver,
+            requested: None,
+            current: None,
+        }
+    }
+
+    /// request the preview of `path`, nothing happens if it is already requested
+    pub fn request(&mut self, path: &Path) {
+        if self.requested.as_deref() == Some(path) {
+            return;
+        }
+        self.requested = Some(path.to_path_buf());
+        let _ = self.requests.send(path.to_path_buf());
+    }
+
+    /// true while the requested preview is loaded
+    pub fn is_loading(&self) -> bool {
+        self.requested.is_some()
+            && self.current.as_ref().map(|preview| &preview.path) != self.requested.as_ref()
+    }
+
+    /// the preview of the requested path, None while it is still loading
+    pub fn current(&mut self) -> Option<&Preview> {
+        while let Ok(preview) = self.results.try_recv() {
+            self.current = Some(preview);
+        }
+        self.current
+            .as_ref()
+            .filter(|preview| Some(&preview.path) == self.requested.as_ref())
+    }
+}
+
+/// Syntax highlighting for text previews, the syntax definitions are loaded on first use
+struct Highlighter {
+    syntaxes: Option<(SyntaxSet, Theme)>,
+}
+
+impl Highlighter {
+    fn new() -> Highlighter {
+        Highlighter { syntaxes: None }
+    }
+
+    /// highlight `text` according to the extension of `path` or its first line.
+    /// None if no syntax is known for it.
+    fn highlight(&mut self, path: &Path, text: &str) -> Option<Vec<Line<'static>>> {
+        let (syntaxes, theme) = self.syntaxes.get_or_insert_with(|| {
+            let mut themes = ThemeSet::load_defaults();
+            let theme = themes.themes.remove(THEME).unwrap_or_default();
+            (SyntaxSet::load_defaults_nonewlines(), theme)
+        });
+
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default();
+        let syntax = syntaxes.find_syntax_by_extension(extension).or_else(|| {
+            syntaxes.find_syntax_by_first_line(text.lines().next().unwrap_or_default())
+        })?;
+        let mut highlight_lines = HighlightLines::new(syntax, theme);
+
+        let mut lines = Vec::new();
+        for line in text.lines().take(MAX_PREVIEW_LINES) {
+            let line = expand_tabs(line);
+            let ranges = highlight_lines.highlight_line(&line, syntaxes).ok()?;
+            let spans: Vec<Span> = ranges
+                .into_iter()
+                .map(|(style, part)| {
+                    let color = style.foreground;
+                    Span::styled(
+                        part.to_owned(),
+                        Style::new().fg(Color::Rgb(color.r, color.g, color.b)),
+                    )
+                })
+                .collect();
+            lines.push(Line::from(spans));
+        }
+        Some(lines)
+    }
+}
+
+fn load(path: &Path, highlighter: &mut Highlighter) -> Preview {
+    let result = match path.symlink_metadata() {
+        Ok(meta) if meta.is_dir() => load_directory(path),
+        //only a link whose target can't be followed is broken, it may point to a special file
+        Ok(meta) if meta.is_symlink() => match (fs::read_link(path), fs::metadata(path)) {
+            (Ok(target), Ok(target_meta)) if target_meta.is_dir() => {
+                load_directory(path).map(|(mut lines, truncated)| {
+                    lines.insert(0, Line::from(format!("→ {}", target.display())).cyan());
+                    (lines, truncated)
+                })
+            }
+            (Ok(_), Ok(target_meta)) if target_meta.is_file() => load_file(path, highlighter),
+            (Ok(target), Ok(_)) => Ok((
+                vec![
+                    Line::from(format!("→ {}", target.display())).cyan(),
+                    Line::from("special file").dark_gray(),
+                ],
+                false,
+            )),
+            (Ok(target), Err(_)) => Ok((
+                vec![Line::from(format!("→ {} (broken link)", target.display())).red()],
+                false,
+            )),
+            (Err(e), _) => Err(e),
+        },
+        Ok(meta) if meta.is_file() => load_file(path, highlighter),
+        Ok(_) => Ok((vec![Line::from("special file").dark_gray()], false)),
+        Err(e) => Err(e),
+    };
+    let (lines, truncated) =
+        result.unwrap_or_else(|e| (vec![Line::from(e.to_string()).red()], false));
+    Preview {
+        path: path.to_path_buf(),
+        lines,
+        truncated,
+    }
+}
+
+/// the children of a directory, directories first
+fn load_directory(path: &Path) -> io::Result<(Vec<Line<'static>>, bool)> {
+    let mut children: Vec<(bool, String)> = fs::read_dir(path)?
+        .flatten()
+        .map(|entry| {
+            let is_dir = entry.path().is_dir();
+            (is_dir, entry.file_name().to_string_lossy().into_owned())
+        })
+        .collect();
+    children.sort_by(|(a_dir, a_name), (b_dir, b_name)| b_dir.cmp(a_dir).then(a_name.cmp(b_name)));
+
+    let truncated = children.len() > MAX_PREVIEW_LINES;
+    let mut lines: Vec<Line> = children
+        .iter()
+        .take(MAX_PREVIEW_LINES)
+        .map(|(is_dir, name)| {
+            if *is_dir {
+                Line::from(format!("{name}/")).blue()
+            } else {
+                Line::from(name.clone())
+            }
+        })
+        .collect();
+    if truncated {
+        lines.push(
+            Line::from(format!("… and {} more", children.len() - MAX_PREVIEW_LINES)).dark_gray(),
+        );
+    }
+    if lines.is_empty() {
+        lines.push(Line::from("empty directory").dark_gray());
+    }
+    Ok((lines, truncated))
+}
+
+/// the first lines of a text file or a hex dump of a binary file
+fn load_file(path: &Path, highlighter: &mut Highlighter) -> io::Result<(Vec<Line<'static>>, bool)> {
+    let file = fs::File::open(path)?;
+    let size = file.metadata()?.len();
+    let mut content = Vec::new();
+    file.take(MAX_PREVIEW_BYTES).read_to_end(&mut content)?;
+    let truncated = size > MAX_PREVIEW_BYTES;
+
+    let text = match std::str::from_utf8(&content) {
+        Ok(text) => Some(text),
+        //the cap may have cut a character in half
+        Err(e) if e.error_len().is_none() => std::str::from_utf8(&content[..e.valid_up_to()]).ok(),
+        Err(_) => None,
+    };
+    match text {
+        Some("") => Ok((vec![Line::from("empty file").dark_gray()], false)),
+        Some(text) if !text.contains('\0') => {
+            let truncated = truncated || text.lines().count() > MAX_PREVIEW_LINES;
+            let lines = highlighter.highlight(path, text).unwrap_or_else(|| {
+                text.lines()
+                    .take(MAX_PREVIEW_LINES)
+                    .map(|line| Line::from(expand_tabs(line)))
+                    .collect()
+            });
+            Ok((lines, truncated))
+        }
+        _ => Ok((
+            hex_dump(&content),
+            truncated || content.len() > MAX_HEX_BYTES,
+        )),
+    }
+}
+
+/// offset, bytes in hex and the printable ascii characters, like `hexdump -C`
+fn hex_dump(content: &[u8]) -> Vec<Line<'static>> {
+    content
+        .chunks(HEX_BYTES_PER_LINE)
+        .take(MAX_HEX_BYTES / HEX_BYTES_PER_LINE)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let hex: Vec<String> = chunk.iter().map(|byte| format!("{byte:02x}")).collect();
+            let ascii: String = chunk
+                .iter()
+                .map(|byte| {
+                    if byte.is_ascii_graphic() || *byte == b' ' {
+                        *byte as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            Line::from(vec![
+                Span::from(format!("{:08x}  ", i * HEX_BYTES_PER_LINE)).dark_gray(),
+                Span::from(format!("{:<48}", hex.join(" "))),
+                Span::from(format!(" |{ascii}|")).dark_gray(),
+            ])
+        })
+        .collect()
+}
+
+fn expand_tabs(line: &str) -> String {
+    line.replace('\t', "    ")
+}
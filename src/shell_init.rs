@@ -0,0 +1,37 @@
+//! Shell functions printed by `--shell-init <shell>`. The `pe` function starts the explorer and
+//! changes the directory of the shell to the one the explorer was quit in.
+
+const POSIX: &str = r#"pe() {
+    local tmp dir
+    tmp="$(mktemp)" || return
+    command pathexplorer --choosedir "$tmp" "$@"
+    dir="$(cat -- "$tmp")"
+    rm -f -- "$tmp"
+    if [ -d "$dir" ] && [ "$dir" != "$PWD" ]; then
+        cd -- "$dir" || return
+    fi
+}
+"#;
+
+const FISH: &str = r#"function pe
+    set -l tmp (mktemp); or return
+    command pathexplorer --choosedir $tmp $argv
+    set -l dir (cat -- $tmp)
+    rm -f -- $tmp
+    if test -d "$dir"; and test "$dir" != "$PWD"
+        cd -- $dir
+    end
+end
+"#;
+
+/// the shells with a function
+pub const SHELLS: &[&str] = &["bash", "zsh", "fish"];
+
+/// the function for `shell`, None for an unknown shell
+pub fn script(shell: &str) -> Option<&'static str> {
+    match shell {
+        "bash" | "zsh" => Some(POSIX),
+        "fish" => Some(FISH),
+        _ => None,
+    }
+}
@@ -24,10 +24,6 @@ impl StringRingBuffer {
         self.buffer
             .push_back(format!("[ERROR {}]: {}", self.error_count, s));
     }
-
-    pub fn clear(&mut self) {
-        self.buffer.clear();
-    }
 }
 impl fmt::Display for StringRingBuffer {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -1,2 +1,1165 @@
+/// an empty directory for the files of the test `name`, a left over one is removed first
+#[cfg(test)]
+fn temp_root(name: &str) -> std::path::PathBuf {
+    let root =
+        std::env::temp_dir().join(format!("pathexplorer-{name}-test-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&root).unwrap();
+    root
+}
+
+/// a FileManager listing `dir` that keeps its recent folders, marks and trash in `root`
+#[cfg(test)]
+fn file_manager(
+    root: &std::path::Path,
+    dir: std::path::PathBuf,
+) -> crate::file_manager::FileManager {
+    let (recent_dirs, _) = crate::navigation::RecentDirs::load(root.join("recent_dirs"));
+    let (bookmarks, _) = crate::bookmarks::Bookmarks::load(root.join("bookmarks"));
+    let trash = crate::trash::Trash::new(Some(root.join("trash")));
+    let config = crate::config::Config::default();
+    crate::file_manager::FileManager::with_state(dir, config, recent_dirs, bookmarks, trash)
+}
+
 #[test]
 fn test_file_manager() {}
+
+#[test]
+fn test_relative_paths() {
+    use std::path::{Path, PathBuf};
+
+    let root = temp_root("paths");
+    let files = root.join("files");
+    std::fs::create_dir_all(files.join("sub")).unwrap();
+    std::fs::write(files.join("a.txt"), b"a").unwrap();
+    let process_dir = std::env::current_dir().unwrap();
+    let mut fm = file_manager(&root, files.clone());
+    assert_eq!(fm.num_files, 2);
+
+    //relative paths start at the listed directory and `..` is resolved
+    fm.change_dir(PathBuf::from("sub"));
+    assert_eq!(fm.current_dir(), files.join("sub"));
+    fm.change_dir(PathBuf::from("../sub/.."));
+    assert_eq!(fm.current_dir(), files);
+    fm.change_dir(PathBuf::from("sub"));
+    fm.create_file(PathBuf::from("new.txt"));
+    assert!(files.join("sub/new.txt").is_file());
+    assert_eq!(fm.index_of(Path::new("./new.txt")), Some(0));
+    fm.add_to_selection(PathBuf::from("../a.txt"));
+    assert_eq!(fm.get_selection(), [files.join("a.txt")]);
+
+    //the process wide directory is never touched
+    assert_eq!(std::env::current_dir().unwrap(), process_dir);
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_go_back() {
+    let root = temp_root("back");
+    let files = root.join("files");
+    std::fs::create_dir_all(files.join("sub")).unwrap();
+    let mut fm = file_manager(&root, files.clone());
+
+    fm.change_dir(files.join("sub"));
+    fm.go_back();
+    assert_eq!(fm.current_dir(), files);
+    fm.go_forward();
+    assert_eq!(fm.current_dir(), files.join("sub"));
+    //there is nothing to go forward to, the directory stays
+    fm.go_forward();
+    assert_eq!(fm.current_dir(), files.join("sub"));
+    assert!(fm.recent_dirs().contains(&files.join("sub")));
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_parent_listing() {
+    let root = temp_root("listing");
+    let files = root.join("files");
+    std::fs::create_dir_all(files.join("sub")).unwrap();
+    std::fs::write(files.join("a.txt"), b"a").unwrap();
+    let mut fm = file_manager(&root, files.join("sub"));
+
+    //the parent listing of the miller columns
+    fm.load_listing(&files);
+    assert_eq!(fm.listing(&files).len(), 2);
+    assert!(fm.listing(&files.join("sub")).is_empty());
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_tree_view() {
+    use crate::filter::{Filter, FilterMode};
+    use std::path::Path;
+
+    let root = temp_root("tree");
+    let files = root.join("files");
+    std::fs::create_dir_all(files.join("sub")).unwrap();
+    std::fs::write(files.join("a.txt"), b"a").unwrap();
+    std::fs::write(files.join("sub/new.txt"), b"new").unwrap();
+    let mut fm = file_manager(&root, files.clone());
+
+    //the expanded directories of the tree view stay expanded after a refresh
+    fm.toggle_tree_view();
+    fm.toggle_expanded(Path::new("sub"));
+    fm.update();
+    let mut depths = fm.tree_depths().to_vec();
+    depths.sort();
+    assert_eq!(depths, [0, 0, 1]);
+    assert!(fm.index_of(Path::new("sub/new.txt")).is_some());
+
+    //a filter in the tree view keeps the expanded children of the matching entries
+    fm.set_filter(Some(Filter::new("a.txt", FilterMode::Substring).unwrap()));
+    assert_eq!(fm.num_files, 1);
+    assert_eq!(fm.tree_depths(), [0]);
+    fm.set_filter(Some(Filter::new("sub", FilterMode::Substring).unwrap()));
+    assert_eq!(fm.num_files, 2);
+    assert_eq!(fm.tree_depths(), [0, 1]);
+    fm.set_filter(None);
+    assert_eq!(fm.num_files, 3);
+    assert!(fm.index_of(Path::new("sub/new.txt")).is_some());
+    assert!(fm.index_of(Path::new("a.txt")).is_some());
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_bulk_rename() {
+    use std::fs;
+
+    let root = temp_root("rename");
+    let files = root.join("files");
+    fs::create_dir_all(&files).unwrap();
+    for name in ["a", "b", "c", "x"] {
+        fs::write(files.join(name), name).unwrap();
+    }
+    let mut fm = file_manager(&root, files.clone());
+    let sources = |names: &[&str]| {
+        names
+            .iter()
+            .map(|name| files.join(name))
+            .collect::<Vec<_>>()
+    };
+    let names = |names: &[&str]| {
+        names
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>()
+    };
+    let content = |name: &str| fs::read_to_string(files.join(name)).unwrap();
+
+    //swap a and b
+    let plan = fm
+        .plan_bulk_rename(&sources(&["a", "b"]), &names(&["b", "a"]))
+        .unwrap();
+    fm.bulk_rename(plan);
+    assert_eq!(
+        (content("a"), content("b")),
+        ("b".to_owned(), "a".to_owned())
+    );
+
+    //a 3-cycle a→b→c→a, unchanged names are left out of the plan
+    let plan = fm
+        .plan_bulk_rename(
+            &sources(&["a", "b", "c", "x"]),
+            &names(&["b", "c", "a", "x"]),
+        )
+        .unwrap();
+    assert_eq!(plan.len(), 3);
+    fm.bulk_rename(plan);
+    assert_eq!(content("a"), "c");
+    assert_eq!(content("b"), "b");
+    assert_eq!(content("c"), "a");
+
+    //two entries can't get the same name and existing entries aren't replaced
+    assert!(
+        fm.plan_bulk_rename(&sources(&["a", "b"]), &names(&["y", "y"]))
+            .is_err()
+    );
+    assert!(
+        fm.plan_bulk_rename(&sources(&["a"]), &names(&["x"]))
+            .is_err()
+    );
+    assert!(
+        fm.plan_bulk_rename(&sources(&["a", "b"]), &names(&["y"]))
+            .is_err()
+    );
+    assert_eq!(fs::read_dir(&files).unwrap().count(), 4);
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_trash_path_encoding() {
+    use crate::trash::unix::{percent_decode, percent_encode};
+
+    let path = "/home/user/some dir/ä%file.txt";
+    let encoded = percent_encode(path.as_bytes());
+    assert_eq!(encoded, "/home/user/some%20dir/%C3%A4%25file.txt");
+    assert_eq!(percent_decode(&encoded), path.as_bytes());
+}
+
+#[test]
+fn test_trash() {
+    use crate::trash::{self, Trash};
+    use std::fs;
+    use std::io::ErrorKind;
+
+    let root = temp_root("trash");
+    let files = root.join("files");
+    fs::create_dir_all(files.join("other")).unwrap();
+    fs::write(files.join("a b.txt"), "first").unwrap();
+    fs::write(files.join("other/a b.txt"), "second").unwrap();
+    let home = root.join("Trash");
+    let trash = Trash::new(Some(home.clone()));
+    //only the entries of the home trash of the test, not the ones of the mount points
+    let listed = || {
+        let (entries, errors) = trash.list();
+        assert!(errors.is_empty());
+        entries
+            .into_iter()
+            .filter(|entry| entry.file.starts_with(&home))
+            .collect::<Vec<_>>()
+    };
+
+    let first = trash.move_to_trash(&files.join("a b.txt")).unwrap();
+    assert!(!files.join("a b.txt").exists());
+    assert_eq!(first.file, home.join("files/a b.txt"));
+    let info = fs::read_to_string(&first.info).unwrap();
+    assert!(info.starts_with("[Trash Info]\nPath="));
+    assert!(info.contains("/files/a%20b.txt\nDeletionDate="));
+    //the second item with the same name gets a free name
+    let second = trash.move_to_trash(&files.join("other/a b.txt")).unwrap();
+    assert_eq!(second.file, home.join("files/a b.txt.2"));
+
+    let mut entries = listed();
+    entries.sort_by(|a, b| a.file.cmp(&b.file));
+    let originals: Vec<_> = entries.iter().map(|entry| entry.original.clone()).collect();
+    assert_eq!(
+        originals,
+        [files.join("a b.txt"), files.join("other/a b.txt")]
+    );
+
+    trash::restore(&first).unwrap();
+    assert_eq!(fs::read_to_string(files.join("a b.txt")).unwrap(), "first");
+    assert!(!first.info.exists());
+    //an existing file is never replaced by a restore
+    fs::write(files.join("other/a b.txt"), "new").unwrap();
+    let error = trash::restore(&second).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::AlreadyExists);
+    assert_eq!(
+        fs::read_to_string(files.join("other/a b.txt")).unwrap(),
+        "new"
+    );
+    assert_eq!(listed().len(), 1);
+
+    trash::purge(&second).unwrap();
+    assert!(!second.file.exists() && !second.info.exists());
+    assert!(listed().is_empty());
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_copy_job() {
+    use crate::file_operations;
+    use crate::jobs::{JobManager, JobStatus};
+    use crate::trash::Trash;
+    use std::collections::HashMap;
+
+    let root = temp_root("job");
+    let src = root.join("src");
+    let dest = root.join("dest");
+    std::fs::create_dir_all(src.join("sub")).unwrap();
+    std::fs::create_dir_all(&dest).unwrap();
+    std::fs::write(src.join("a.txt"), vec![1u8; 3 * 1024 * 1024]).unwrap();
+    std::fs::write(src.join("sub/b.txt"), b"hello").unwrap();
+
+    let mut jobs = JobManager::new();
+    let selection = vec![src.clone()];
+    let target = dest.clone();
+    let trash = Trash::new(Some(root.join("trash")));
+    jobs.spawn(String::from("copy"), vec![dest.clone()], move |ctx| {
+        file_operations::paste(&selection, &target, false, &HashMap::new(), &trash, ctx)
+    });
+
+    let mut finished = Vec::new();
+    while finished.is_empty() {
+        let (errors, done) = jobs.poll();
+        assert!(errors.is_empty());
+        finished = done;
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+
+    let job = &jobs.jobs()[0];
+    assert!(job.status == JobStatus::Finished);
+    assert_eq!(job.progress.bytes_done, 3 * 1024 * 1024 + 5);
+    assert_eq!(job.progress.files_done, job.progress.files_total);
+    assert_eq!(finished[0].actions.len(), 1);
+    assert_eq!(std::fs::read(dest.join("src/sub/b.txt")).unwrap(), b"hello");
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_cancelled_copy() {
+    use crate::file_operations;
+    use crate::jobs::JobManager;
+    use crate::trash::Trash;
+    use std::collections::HashMap;
+
+    let root = temp_root("cancel");
+    let src = root.join("src");
+    let dest = root.join("dest");
+    for dir in 0..10 {
+        std::fs::create_dir_all(src.join(format!("dir{dir}"))).unwrap();
+        for file in 0..200 {
+            std::fs::write(src.join(format!("dir{dir}/file{file}")), b"x").unwrap();
+        }
+    }
+    std::fs::create_dir_all(&dest).unwrap();
+
+    let mut jobs = JobManager::new();
+    let selection = vec![src.clone()];
+    let target = dest.clone();
+    let trash = Trash::new(Some(root.join("trash")));
+    jobs.spawn(String::from("copy"), vec![dest.clone()], move |ctx| {
+        file_operations::paste(&selection, &target, false, &HashMap::new(), &trash, ctx)
+    });
+    //cancel as soon as the copy of the directory was started
+    while !dest.join("src").exists() {
+        std::thread::yield_now();
+    }
+    jobs.cancel(0);
+    let finished = loop {
+        let (_, finished) = jobs.poll();
+        if !finished.is_empty() {
+            break finished;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    };
+
+    //the partial copy is gone and nothing was recorded for the journal
+    assert!(finished[0].actions.is_empty());
+    assert!(!dest.join("src").exists());
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_paste_conflicts() {
+    use crate::file_operations::{self, ConflictPolicy, MergeFiles};
+    use crate::jobs::JobContext;
+    use crate::trash::Trash;
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::Path;
+
+    let root = temp_root("conflict");
+    let src = root.join("src");
+    let dest = root.join("dest");
+    let trash = Trash::new(Some(root.join("trash")));
+    let read = |path: &Path| fs::read_to_string(path).unwrap();
+
+    //src/d and dest/d both contain same.txt and inner/same.txt, f.txt exists on both sides
+    let setup = || {
+        let _ = fs::remove_dir_all(&src);
+        let _ = fs::remove_dir_all(&dest);
+        fs::create_dir_all(src.join("d/inner")).unwrap();
+        fs::create_dir_all(dest.join("d/inner")).unwrap();
+        for dir in [&src, &dest] {
+            let content = if dir == &src { "new" } else { "old" };
+            fs::write(dir.join("d/same.txt"), content).unwrap();
+            fs::write(dir.join("d/inner/same.txt"), content).unwrap();
+            fs::write(dir.join("f.txt"), content).unwrap();
+        }
+        fs::write(src.join("d/only.txt"), "new").unwrap();
+    };
+    let paste = |policy: ConflictPolicy, cut: bool| {
+        let selection = [src.join("d"), src.join("f.txt")];
+        let resolutions: HashMap<_, _> = selection.iter().map(|p| (p.clone(), policy)).collect();
+        let mut ctx = JobContext::detached();
+        file_operations::paste(&selection, &dest, cut, &resolutions, &trash, &mut ctx);
+        assert_eq!(ctx.error_count(), 0);
+    };
+
+    setup();
+    assert_eq!(
+        file_operations::unique_name(&dest.join("f.txt")),
+        dest.join("f (1).txt")
+    );
+    fs::write(dest.join("f (1).txt"), "").unwrap();
+    assert_eq!(
+        file_operations::unique_name(&dest.join("f.txt")),
+        dest.join("f (2).txt")
+    );
+    assert_eq!(
+        file_operations::unique_name(&dest.join("d")),
+        dest.join("d (1)")
+    );
+
+    //the policy of a merge applies to the nested conflicts and to files with apply to all
+    setup();
+    paste(ConflictPolicy::Merge(MergeFiles::Skip), false);
+    assert_eq!(read(&dest.join("d/same.txt")), "old");
+    assert_eq!(read(&dest.join("d/inner/same.txt")), "old");
+    assert_eq!(read(&dest.join("d/only.txt")), "new");
+    assert_eq!(read(&dest.join("f.txt")), "old");
+    assert!(!dest.join("d/same (1).txt").exists());
+
+    setup();
+    paste(ConflictPolicy::Merge(MergeFiles::Rename), false);
+    assert_eq!(read(&dest.join("d/same.txt")), "old");
+    assert_eq!(read(&dest.join("d/same (1).txt")), "new");
+    assert_eq!(read(&dest.join("d/inner/same (1).txt")), "new");
+    assert_eq!(read(&dest.join("f.txt")), "old");
+    assert_eq!(read(&dest.join("f (1).txt")), "new");
+
+    setup();
+    paste(ConflictPolicy::Merge(MergeFiles::Overwrite), true);
+    assert_eq!(read(&dest.join("d/same.txt")), "new");
+    assert_eq!(read(&dest.join("d/inner/same.txt")), "new");
+    assert_eq!(read(&dest.join("f.txt")), "new");
+    //the replaced files went to the trash of the test
+    let (trashed, _) = trash.list();
+    assert!(
+        trashed
+            .iter()
+            .any(|entry| entry.original == dest.join("d/inner/same.txt")
+                && read(&entry.file) == "old")
+    );
+    //everything was moved out of the source
+    assert!(!src.join("d").exists());
+    assert!(!src.join("f.txt").exists());
+
+    //skipped entries stay in the source of a move
+    setup();
+    paste(ConflictPolicy::Merge(MergeFiles::Skip), true);
+    assert_eq!(read(&src.join("d/inner/same.txt")), "new");
+    assert_eq!(read(&dest.join("d/only.txt")), "new");
+    assert!(!src.join("d/only.txt").exists());
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_move_path() {
+    use crate::file_operations::{self, move_path};
+    use crate::jobs::JobContext;
+    use crate::journal::Action;
+    use crate::trash::Trash;
+    use std::collections::HashMap;
+    use std::fs;
+
+    let root = temp_root("move");
+    fs::create_dir_all(root.join("from/d/inner")).unwrap();
+    fs::create_dir_all(root.join("to")).unwrap();
+    fs::write(root.join("from/d/inner/f.txt"), "content").unwrap();
+    let mut ctx = JobContext::detached();
+
+    //a directory is moved with its content into another directory
+    move_path(&root.join("from/d"), &root.join("to/d"), &mut ctx).unwrap();
+    assert!(!root.join("from/d").exists());
+    assert_eq!(
+        fs::read_to_string(root.join("to/d/inner/f.txt")).unwrap(),
+        "content"
+    );
+
+    //a directory can't be moved into itself, neither directly nor into a subdirectory
+    for dest in [root.join("to/d/d"), root.join("to/d/inner/d")] {
+        assert!(move_path(&root.join("to/d"), &dest, &mut ctx).is_err());
+        assert!(!dest.exists());
+    }
+    assert!(root.join("to/d/inner/f.txt").exists());
+
+    //an existing destination is never replaced
+    fs::write(root.join("from/f.txt"), "other").unwrap();
+    assert!(
+        move_path(
+            &root.join("from/f.txt"),
+            &root.join("to/d/inner/f.txt"),
+            &mut ctx
+        )
+        .is_err()
+    );
+    assert_eq!(
+        fs::read_to_string(root.join("to/d/inner/f.txt")).unwrap(),
+        "content"
+    );
+    assert!(root.join("from/f.txt").exists());
+
+    //a cut paste moves the selection and records the moves for the journal
+    let selection = [root.join("from/f.txt"), root.join("to/d")];
+    let (_, actions) = file_operations::paste(
+        &selection,
+        &root.join("from"),
+        true,
+        &HashMap::new(),
+        &Trash::new(Some(root.join("trash"))),
+        &mut ctx,
+    );
+    assert_eq!(ctx.error_count(), 0);
+    assert!(root.join("from/d/inner/f.txt").exists());
+    assert!(!root.join("to/d").exists());
+    //the file was already in the target directory and stays where it is
+    assert!(matches!(
+        actions.as_slice(),
+        [Action::Moved { from, to }] if *from == root.join("to/d") && *to == root.join("from/d")
+    ));
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_journal() {
+    use crate::jobs::JobContext;
+    use crate::journal::{Action, Journal, JournalEntry};
+    use crate::trash::Trash;
+    use std::fs;
+
+    let root = temp_root("journal");
+    let trash = Trash::new(Some(root.join("trash")));
+    let mut journal = Journal::new();
+    let mut ctx = JobContext::detached();
+    let descriptions = |entries: Vec<&JournalEntry>| -> Vec<String> {
+        entries
+            .iter()
+            .map(|entry| entry.description.clone())
+            .collect()
+    };
+
+    //undo of a trash restores the file, redo trashes it again
+    let file = root.join("trashed.txt");
+    fs::write(&file, "content").unwrap();
+    let entry = trash.move_to_trash(&file).unwrap();
+    journal.record(String::from("trash"), vec![Action::Trashed(entry)]);
+    journal.undo(1, &trash, &mut ctx);
+    assert_eq!(fs::read_to_string(&file).unwrap(), "content");
+    journal.redo(1, &trash, &mut ctx);
+    assert!(!file.exists());
+    //only the trash of the test is used
+    let trashed = || fs::read_dir(root.join("trash/files")).unwrap().count();
+    assert_eq!(trashed(), 1);
+    journal.undo(1, &trash, &mut ctx);
+    assert!(file.exists());
+    assert_eq!(trashed(), 0);
+    fs::remove_file(&file).unwrap();
+
+    //a swap through a temporary name is reverted in reverse order
+    fs::write(root.join("a"), "a").unwrap();
+    fs::write(root.join("b"), "b").unwrap();
+    let mut actions = Vec::new();
+    for (from, to) in [("a", "tmp"), ("b", "a"), ("tmp", "b")] {
+        fs::rename(root.join(from), root.join(to)).unwrap();
+        actions.push(Action::Moved {
+            from: root.join(from),
+            to: root.join(to),
+        });
+    }
+    journal.record(String::from("swap"), actions);
+    //a later move of the swapped file has to be undone first
+    fs::rename(root.join("a"), root.join("c")).unwrap();
+    journal.record(
+        String::from("move"),
+        vec![Action::Moved {
+            from: root.join("a"),
+            to: root.join("c"),
+        }],
+    );
+    assert_eq!(
+        descriptions(journal.undo_entries().collect()),
+        ["move", "swap"]
+    );
+
+    journal.undo(2, &trash, &mut ctx);
+    assert_eq!(ctx.error_count(), 0);
+    assert_eq!(fs::read_to_string(root.join("a")).unwrap(), "a");
+    assert_eq!(fs::read_to_string(root.join("b")).unwrap(), "b");
+    assert!(!root.join("c").exists() && !root.join("tmp").exists());
+    assert_eq!(journal.undo_entries().count(), 0);
+    assert_eq!(
+        descriptions(journal.redo_entries().collect()),
+        ["swap", "move"]
+    );
+
+    //redo replays the operations in their original order
+    journal.redo(2, &trash, &mut ctx);
+    assert_eq!(ctx.error_count(), 0);
+    assert_eq!(fs::read_to_string(root.join("b")).unwrap(), "a");
+    assert_eq!(fs::read_to_string(root.join("c")).unwrap(), "b");
+    assert!(!root.join("a").exists());
+
+    //a new operation discards what was undone
+    journal.undo(1, &trash, &mut ctx);
+    journal.record(String::from("other"), vec![Action::Created(root.join("b"))]);
+    assert_eq!(journal.redo_entries().count(), 0);
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_preview_links() {
+    use crate::preview::Previewer;
+    use std::os::unix::fs::symlink;
+    use std::os::unix::net::UnixListener;
+    use std::path::Path;
+    use std::time::{Duration, Instant};
+
+    let root = temp_root("preview");
+    std::fs::write(root.join("file.txt"), "hello").unwrap();
+    let _socket = UnixListener::bind(root.join("socket")).unwrap();
+    symlink(root.join("file.txt"), root.join("to_file")).unwrap();
+    symlink(root.join("socket"), root.join("to_socket")).unwrap();
+    symlink(root.join("missing"), root.join("dangling")).unwrap();
+
+    let mut previewer = Previewer::new();
+    let mut preview_of = |path: &Path| -> Vec<String> {
+        previewer.request(path);
+        let start = Instant::now();
+        loop {
+            if let Some(preview) = previewer.current()
+                && preview.path == path
+            {
+                return preview.lines.iter().map(|line| line.to_string()).collect();
+            }
+            assert!(start.elapsed() < Duration::from_secs(5));
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    };
+
+    assert_eq!(preview_of(&root.join("to_file")), ["hello"]);
+    let special = preview_of(&root.join("to_socket"));
+    assert!(!special.concat().contains("broken link"));
+    let dangling = preview_of(&root.join("dangling"));
+    assert!(dangling[0].ends_with("(broken link)"));
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_fuzzy_match() {
+    use crate::fuzzy::fuzzy_match;
+
+    assert!(fuzzy_match("xyz", "src/main.rs").is_none());
+    let found = fuzzy_match("main", "src/main.rs").unwrap();
+    assert_eq!(found.positions, vec![4, 5, 6, 7]);
+    //consecutive matches in the file name beat scattered ones
+    let exact = fuzzy_match("fm", "src/fm.rs").unwrap();
+    let scattered = fuzzy_match("fm", "src/file_operations/mod.rs").unwrap();
+    assert!(exact.score > scattered.score);
+    //smart case
+    assert!(fuzzy_match("Main", "src/main.rs").is_none());
+}
+
+#[test]
+fn test_filter() {
+    use crate::filter::{Filter, FilterMode};
+
+    let substring = Filter::new("main", FilterMode::Substring).unwrap();
+    assert!(substring.matches("Main.rs"));
+    assert!(
+        !Filter::new("Main", FilterMode::Substring)
+            .unwrap()
+            .matches("main.rs")
+    );
+
+    let glob = Filter::new("*.rs", FilterMode::Glob).unwrap();
+    assert!(glob.matches("main.RS"));
+    assert!(!glob.matches("main.rs.bak"));
+
+    let regex = Filter::new(r"^\d+_", FilterMode::Regex).unwrap();
+    assert!(regex.matches("01_intro.md"));
+    assert!(!regex.matches("intro_01.md"));
+    assert!(Filter::new("(", FilterMode::Regex).is_err());
+}
+
+#[test]
+fn test_column_layout() {
+    use crate::columns::{Column, elide, layout_widths, tree_guides};
+
+    assert_eq!(
+        tree_guides(&[0, 1, 2, 1, 0]),
+        ["", "  ├─ ", "  │    └─ ", "  └─ ", ""]
+    );
+
+    assert_eq!(elide("short.txt", 20), "short.txt");
+    let elided = elide("a_really_long_file_name.txt", 12);
+    assert_eq!(elided, "a_really…txt");
+
+    let columns = [
+        (Column::Name, 30),
+        (Column::Size, 6),
+        (Column::Permissions, 11),
+    ];
+    //everything fits, the name gets the remaining space
+    assert_eq!(
+        layout_widths(&columns, 60, 16),
+        vec![
+            (Column::Name, 41),
+            (Column::Size, 6),
+            (Column::Permissions, 11)
+        ]
+    );
+    //too narrow, the last column is dropped
+    assert_eq!(
+        layout_widths(&columns, 30, 16),
+        vec![(Column::Name, 23), (Column::Size, 6)]
+    );
+}
+
+#[test]
+fn test_sizes() {
+    use crate::dir_size::{DirSizes, directory_size};
+    use crate::util::format_bytes;
+
+    assert_eq!(format_bytes(512, false), "512 B");
+    assert_eq!(format_bytes(1536, false), "1.5 KiB");
+    assert_eq!(format_bytes(1500, true), "1.5 kB");
+    assert_eq!(format_bytes(3 * 1024 * 1024, false), "3.0 MiB");
+
+    let root = temp_root("size");
+    std::fs::create_dir_all(root.join("a/b")).unwrap();
+    std::fs::write(root.join("a/one"), [0u8; 100]).unwrap();
+    std::fs::write(root.join("a/b/two"), [0u8; 50]).unwrap();
+    //hard links are only counted once
+    std::fs::hard_link(root.join("a/one"), root.join("a/b/link")).unwrap();
+    assert_eq!(directory_size(&root, || true), Some(150));
+    assert_eq!(directory_size(&root, || false), None);
+
+    //only the changed path, its ancestors and its descendants are forgotten
+    let mut sizes = DirSizes::new();
+    std::fs::create_dir_all(root.join("c")).unwrap();
+    let dirs = vec![
+        root.clone(),
+        root.join("a"),
+        root.join("a/b"),
+        root.join("c"),
+    ];
+    sizes.request(dirs.clone());
+    while dirs.iter().any(|dir| sizes.get(dir).is_none()) {
+        sizes.poll();
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    sizes.invalidate(&[root.join("a/one")]);
+    assert_eq!(sizes.get(&root), None);
+    assert_eq!(sizes.get(&root.join("a")), None);
+    assert_eq!(sizes.get(&root.join("a/b")), Some(150));
+    assert_eq!(sizes.get(&root.join("c")), Some(0));
+    sizes.invalidate(&[root.join("a")]);
+    assert_eq!(sizes.get(&root.join("a/b")), None);
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_redo_dir_sizes() {
+    use crate::file_manager::FileManager;
+    use std::path::Path;
+    use std::time::{Duration, Instant};
+
+    let root = temp_root("redo");
+    let files = root.join("files");
+    for dir in ["a", "b"] {
+        std::fs::create_dir_all(files.join(dir)).unwrap();
+        std::fs::write(files.join(dir).join("f"), b"x").unwrap();
+    }
+    let mut fm = file_manager(&root, files.clone());
+    let wait_for_sizes = |fm: &mut FileManager| {
+        let start = Instant::now();
+        while fm.dir_size(Path::new("a")).is_none() || fm.dir_size(Path::new("b")).is_none() {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            fm.poll_dir_sizes();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    };
+
+    fm.toggle_dir_sizes();
+    fm.rename(Path::new("a/f"), "g");
+    fm.rename(Path::new("b/f"), "g");
+    fm.undo(2);
+    wait_for_sizes(&mut fm);
+    //the rename in a was undone last, so it is replayed first
+    fm.redo(1);
+    assert!(files.join("a/g").exists() && files.join("b/f").exists());
+    assert_eq!(fm.dir_size(Path::new("a")), None);
+    assert_eq!(fm.dir_size(Path::new("b")), Some(1));
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_disk_usage() {
+    use crate::disk_usage::Scan;
+
+    let root = temp_root("usage");
+    std::fs::create_dir_all(root.join("big/inner")).unwrap();
+    std::fs::create_dir_all(root.join("small")).unwrap();
+    std::fs::write(root.join("big/inner/a"), [0u8; 300]).unwrap();
+    std::fs::write(root.join("big/b"), [0u8; 100]).unwrap();
+    std::fs::write(root.join("small/c"), [0u8; 50]).unwrap();
+    std::fs::write(root.join("d"), [0u8; 200]).unwrap();
+
+    let scan = Scan::start(root.clone());
+    let mut tree = loop {
+        if let Some(tree) = scan.poll() {
+            break tree;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    };
+    assert_eq!(tree.size, 650);
+    //children are sorted by their cumulative size
+    let names: Vec<_> = tree.children.iter().map(|c| c.name.clone()).collect();
+    assert_eq!(names, ["big", "d", "small"]);
+    assert_eq!(tree.descendant(&[0, 0]).unwrap().size, 300);
+
+    //removing a node shrinks all of its ancestors
+    let removed = tree.remove(&[0, 0]).unwrap();
+    assert_eq!(removed.size, 300);
+    assert_eq!(tree.size, 350);
+    assert_eq!(tree.children[0].size, 100);
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_dir_watcher() {
+    use crate::watcher::DirWatcher;
+    use std::time::{Duration, Instant};
+
+    let root = temp_root("watch");
+    let (mut watcher, error) = DirWatcher::new();
+    assert!(error.is_none());
+    watcher.watch(std::slice::from_ref(&root)).unwrap();
+    assert!(!watcher.poll());
+
+    //a burst of changes is reported once
+    for i in 0..10 {
+        std::fs::write(root.join(format!("file{i}")), b"x").unwrap();
+    }
+    let start = Instant::now();
+    while !watcher.poll() {
+        assert!(start.elapsed() < Duration::from_secs(5));
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    std::thread::sleep(Duration::from_millis(300));
+    assert!(!watcher.poll());
+
+    //the directory of a parked tab is watched and read again as well
+    let shown = root.join("shown");
+    let parked = root.join("parked");
+    std::fs::create_dir_all(&shown).unwrap();
+    std::fs::create_dir_all(&parked).unwrap();
+    let mut fm = file_manager(&root, parked.clone());
+    fm.open_tab(None);
+    fm.change_dir(shown.clone());
+    assert!(fm.tab_entries(0).is_empty());
+    std::fs::write(parked.join("new"), b"x").unwrap();
+    let start = Instant::now();
+    while !fm.poll_watcher() {
+        assert!(start.elapsed() < Duration::from_secs(5));
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(fm.tab_entries(0)[0].file_name(), "new");
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_tabs() {
+    use crate::file_manager::{FileManager, Sorting};
+
+    let root = temp_root("tabs");
+    let (first, second) = (root.join("first"), root.join("second"));
+    std::fs::create_dir_all(&first).unwrap();
+    std::fs::create_dir_all(&second).unwrap();
+    for name in ["a", "b", ".c"] {
+        std::fs::write(first.join(name), b"x").unwrap();
+        std::fs::write(second.join(name), b"x").unwrap();
+    }
+    let mut fm = file_manager(&root, first.clone());
+    let names = |fm: &FileManager| -> Vec<String> {
+        let entries = fm.get_entries().iter();
+        entries
+            .map(|e| e.file_name().to_string_lossy().into_owned())
+            .collect()
+    };
+    fm.sort(Sorting::SortedByNameAscending);
+    fm.add_to_selection(first.join("a"));
+
+    //a new tab starts in the same directory with the same row highlighted
+    assert_eq!(fm.open_tab(Some(1)), Some(1));
+    assert_eq!((fm.tab_count(), fm.current_tab()), (2, 1));
+    assert_eq!(fm.current_dir(), first);
+    fm.change_dir(second.clone());
+    fm.sort(Sorting::SortedByNameDescending);
+    fm.show_hidden = true;
+    fm.update();
+    assert_eq!(names(&fm), ["b", "a", ".c"]);
+    assert_eq!(fm.tab_selected(0), Some(1));
+
+    //every tab keeps its directory, sorting, hidden files and highlighted row
+    assert_eq!(fm.switch_tab(0, Some(2)), Some(1));
+    assert_eq!(fm.current_dir(), first);
+    assert!(!fm.show_hidden);
+    assert_eq!(names(&fm), ["a", "b"]);
+    assert_eq!(fm.tab_selected(1), Some(2));
+    assert_eq!(fm.tab_dirs(), [first.clone(), second.clone()]);
+    assert_eq!(fm.switch_tab(5, Some(0)), Some(0));
+    assert_eq!(fm.current_tab(), 0);
+
+    //the selection is shared
+    fm.add_to_selection(second.join("b"));
+    assert_eq!(fm.switch_tab(1, Some(1)), Some(2));
+    assert!(fm.is_selected(&first.join("a")) && fm.is_selected(&second.join("b")));
+
+    //closing shows the neighbour, the last tab stays
+    assert_eq!(fm.close_tab(Some(2)), Some(1));
+    assert_eq!((fm.tab_count(), fm.current_tab()), (1, 0));
+    assert_eq!(fm.current_dir(), first);
+    assert_eq!(names(&fm), ["a", "b"]);
+    assert_eq!(fm.close_tab(Some(0)), Some(0));
+    assert_eq!(fm.tab_count(), 1);
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_navigation() {
+    use crate::navigation::{History, RecentDirs};
+    use std::path::PathBuf;
+
+    let mut history = History::new();
+    history.visit(PathBuf::from("/a"));
+    history.visit(PathBuf::from("/b"));
+    assert_eq!(history.back(PathBuf::from("/c")), Some(PathBuf::from("/b")));
+    assert_eq!(history.back(PathBuf::from("/b")), Some(PathBuf::from("/a")));
+    assert_eq!(history.back(PathBuf::from("/a")), None);
+    assert_eq!(
+        history.forward(PathBuf::from("/a")),
+        Some(PathBuf::from("/b"))
+    );
+    //visiting a new directory drops the forward stack
+    history.visit(PathBuf::from("/b"));
+    assert_eq!(history.forward(PathBuf::from("/d")), None);
+
+    let root = temp_root("recent");
+    let file = root.join("recent_dirs");
+    let (mut recent, error) = RecentDirs::load(file.clone());
+    assert!(error.is_none());
+    recent.visit(&PathBuf::from("/once")).unwrap();
+    for _ in 0..3 {
+        recent.visit(&PathBuf::from("/often")).unwrap();
+    }
+    //the list survives a restart
+    let (recent, _) = RecentDirs::load(file.clone());
+    let ranked: Vec<PathBuf> = recent.ranked().iter().map(|dir| dir.path.clone()).collect();
+    assert_eq!(ranked, [PathBuf::from("/often"), PathBuf::from("/once")]);
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_bookmarks() {
+    use crate::bookmarks::Bookmarks;
+    use std::path::{Path, PathBuf};
+
+    let root = temp_root("marks");
+    let file = root.join("bookmarks");
+    let (mut bookmarks, error) = Bookmarks::load(file.clone());
+    assert!(error.is_none());
+    bookmarks.set('a', PathBuf::from("/tmp")).unwrap();
+    bookmarks
+        .set('b', PathBuf::from("/home/some user"))
+        .unwrap();
+    //renaming onto an existing mark swaps the two
+    bookmarks.rename('a', 'b').unwrap();
+
+    let (mut bookmarks, _) = Bookmarks::load(file.clone());
+    assert_eq!(bookmarks.get('a'), Some(Path::new("/home/some user")));
+    assert_eq!(bookmarks.get('b'), Some(Path::new("/tmp")));
+    bookmarks.remove('a').unwrap();
+    assert_eq!(bookmarks.marks().count(), 1);
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_keymap() {
+    use crate::keymap::{Action, Key, Keymap, Lookup};
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    let key = |code, modifiers| Key::from_event(&KeyEvent::new(code, modifiers));
+    assert_eq!(
+        Key::parse("ctrl-t"),
+        Some(key(KeyCode::Char('t'), KeyModifiers::CONTROL))
+    );
+    //shift is part of the character
+    assert_eq!(
+        Key::parse("G"),
+        Some(key(KeyCode::Char('G'), KeyModifiers::SHIFT))
+    );
+    assert_eq!(Key::parse("alt-left").unwrap().to_string(), "alt-left");
+    assert_eq!(Key::parse("hyper-x"), None);
+    assert!(Keymap::default_keys().conflicts().is_empty());
+
+    let root = temp_root("keys");
+    let file = root.join("keys.toml");
+    std::fs::write(
+        &file,
+        "go_top = \"g g\"\ntoggle_hidden = \".\"\nquit = [\"q\", \"ctrl-c\"]\nfly = \"F\"\n",
+    )
+    .unwrap();
+    let (keymap, errors) = Keymap::load(file.clone());
+    assert_eq!(errors.len(), 1, "{errors:?}");
+    let g = key(KeyCode::Char('g'), KeyModifiers::NONE);
+    assert!(matches!(keymap.lookup(&[g]), Lookup::Pending));
+    assert!(
+        matches!(keymap.lookup(&[g, g]), Lookup::Actions(actions) if actions == [Action::GoTop])
+    );
+    let ctrl_c = key(KeyCode::Char('c'), KeyModifiers::CONTROL);
+    assert!(
+        matches!(keymap.lookup(&[ctrl_c]), Lookup::Actions(actions) if actions == [Action::Quit])
+    );
+    //<esc> clears the filter and cancels picking otherwise
+    let esc = key(KeyCode::Esc, KeyModifiers::NONE);
+    assert!(matches!(keymap.lookup(&[esc]),
+        Lookup::Actions(actions) if actions == [Action::ClearFilter, Action::CancelPick]));
+    //<n> creates a file unless a filter is set
+    let n = key(KeyCode::Char('n'), KeyModifiers::NONE);
+    assert!(matches!(keymap.lookup(&[n]),
+        Lookup::Actions(actions) if actions == [Action::NextMatch, Action::NewFile]));
+    assert!(
+        keymap
+            .help_lines()
+            .contains(&"<g g> → go to the first entry".to_owned())
+    );
+
+    //the prefix makes the longer binding unreachable
+    std::fs::write(&file, "go_top = \"g g\"\n").unwrap();
+    let (_, errors) = Keymap::load(file.clone());
+    assert_eq!(errors.len(), 1, "{errors:?}");
+
+    //popups share the movement keys, <q> closes them but quits the explorer
+    let event = |code| KeyEvent::new(code, KeyModifiers::NONE);
+    let keymap = Keymap::default_keys();
+    let q = key(KeyCode::Char('q'), KeyModifiers::NONE);
+    assert!(matches!(keymap.lookup(&[q]), Lookup::Actions(actions) if actions == [Action::Quit]));
+    assert_eq!(
+        keymap.popup_action(&event(KeyCode::Char('q'))),
+        Some(Action::Close)
+    );
+    assert_eq!(
+        keymap.popup_action(&event(KeyCode::Down)),
+        Some(Action::Down)
+    );
+    assert_eq!(keymap.popup_action(&event(KeyCode::Char('x'))), None);
+    assert!(keymap.is_bound(&event(KeyCode::Char('D')), Action::DiskUsage));
+    assert_eq!(keymap.hint(Action::Close), "<esc>");
+    std::fs::write(&file, "close = \"j\"\nconfirm = \"c c\"\n").unwrap();
+    let (_, errors) = Keymap::load(file.clone());
+    assert_eq!(errors.len(), 2, "{errors:?}");
+
+    //the keys of a window only clash with the movement keys and the other keys of the window
+    let r = event(KeyCode::Char('r'));
+    assert!(keymap.is_bound(&r, Action::Restore) && keymap.is_bound(&r, Action::RenameMark));
+    assert_eq!(keymap.popup_action(&r), None);
+    assert_eq!(keymap.hint(Action::PauseJob), "<p>");
+    std::fs::write(
+        &file,
+        "pause_job = \"j\"\ncancel_job = \"esc\"\nrescan = \"a\"\nfilter_mode = \"r\"\n",
+    )
+    .unwrap();
+    let (keymap, errors) = Keymap::load(file.clone());
+    assert_eq!(errors.len(), 2, "{errors:?}");
+    assert_eq!(keymap.hint(Action::FilterMode), "<r>");
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_config() {
+    use crate::config::Config;
+    use crate::file_manager::{SortDir, Sorting};
+
+    let root = temp_root("config");
+    let file = root.join("config.toml");
+    //unknown keys are reported with their line
+    std::fs::write(&file, "show_hidden = true\nshow_hiden = true\n").unwrap();
+    let (config, errors) = Config::load(file.clone());
+    assert!(!config.show_hidden);
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].to_string().contains("line 2"), "{}", errors[0]);
+
+    std::fs::write(
+        &file,
+        "# kept\nsorting = \"name_ascending\"\ntable_width = 5\nsave_preferences = true\n",
+    )
+    .unwrap();
+    let (mut config, errors) = Config::load(file.clone());
+    assert!(matches!(config.sorting, Sorting::SortedByNameAscending));
+    assert_eq!(config.table_width, 70);
+    assert_eq!(errors.len(), 1);
+
+    //preferences are written back, the rest of the file stays
+    config
+        .save_preferences(true, Sorting::SortedBySizeDescending, SortDir::Start)
+        .unwrap();
+    let content = std::fs::read_to_string(&file).unwrap();
+    assert!(content.starts_with("# kept\n"), "{content}");
+    assert!(content.contains("table_width = 5"));
+    let (config, _) = Config::load(file.clone());
+    assert!(config.show_hidden);
+    assert!(matches!(config.sorting, Sorting::SortedBySizeDescending));
+    assert!(matches!(config.dir_sorting, SortDir::Start));
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_cli() {
+    use crate::cli::{Command, PickMode, parse};
+    use crate::file_manager::Sorting;
+    use std::ffi::OsString;
+
+    let args = |args: &[&str]| parse(args.iter().map(OsString::from).collect::<Vec<_>>());
+    let dir = temp_root("cli");
+    let file = dir.join("file.txt");
+    std::fs::write(&file, "").unwrap();
+
+    //a file starts in its directory with the file highlighted
+    let Ok(Command::Run(options)) = args(&[
+        "--sort=name_descending",
+        "--read-only",
+        file.to_str().unwrap(),
+    ]) else {
+        panic!("arguments were not accepted");
+    };
+    assert_eq!(options.start_dir, dir);
+    assert_eq!(options.start_file, Some(file.clone()));
+    assert!(matches!(
+        options.sorting,
+        Some(Sorting::SortedByNameDescending)
+    ));
+    assert!(options.read_only && !options.show_hidden);
+
+    let Ok(Command::Run(options)) = args(&["--choosedir", "/tmp/last-dir", "--print-cwd"]) else {
+        panic!("arguments were not accepted");
+    };
+    assert_eq!(
+        options.choosedir,
+        Some(std::path::PathBuf::from("/tmp/last-dir"))
+    );
+    assert!(options.print_cwd && options.start_file.is_none());
+    assert!(matches!(args(&["--shell-init", "fish"]),
+        Ok(Command::ShellInit(script)) if script.contains("--choosedir")));
+    assert!(args(&["--shell-init=tcsh"]).is_err());
+
+    //picking never changes files
+    let Ok(Command::Run(options)) = args(&["--pick=dirs", "--multiple", "-0"]) else {
+        panic!("arguments were not accepted");
+    };
+    assert_eq!(options.pick, Some(PickMode::Dirs));
+    assert!(options.multiple && options.null && options.read_only);
+    assert!(matches!(args(&["--pick"]),
+        Ok(Command::Run(options)) if options.pick == Some(PickMode::Any)));
+    assert!(args(&["--pick=everything"]).is_err());
+    assert!(args(&["--multiple"]).is_err());
+    assert!(args(&["--pick", "--print-cwd"]).is_err());
+
+    assert!(matches!(args(&["-h"]), Ok(Command::Help)));
+    assert!(matches!(args(&["--version"]), Ok(Command::Version)));
+    assert!(args(&["--sort", "by_color"]).is_err());
+    assert!(args(&["--sort"]).is_err());
+    assert!(args(&["--read-only=yes"]).is_err());
+    assert!(args(&["--frobnicate"]).is_err());
+    assert!(args(&[dir.join("missing").to_str().unwrap()]).is_err());
+    assert!(args(&["--config", dir.join("missing.toml").to_str().unwrap()]).is_err());
+    std::fs::remove_dir_all(dir).unwrap();
+}
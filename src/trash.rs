@@ -0,0 +1,113 @@
+//! Moving files into the trash and back.
+//!
+//! On unix the freedesktop.org Trash specification is implemented in [`unix`], other platforms
+//! have no trash and refuse to move files into it.
+
+use crate::util;
+use std::fs;
+use std::io::{self, Error, ErrorKind};
+use std::path::{Path, PathBuf};
+
+#[cfg(unix)]
+pub mod unix;
+#[cfg(unix)]
+use unix::{list, move_to_trash};
+
+/// A single item inside a trash directory.
+#[derive(Clone)]
+pub struct TrashEntry {
+    /// absolute path the item was trashed from
+    pub original: PathBuf,
+    /// the trashed file itself, inside `<trash>/files`
+    pub file: PathBuf,
+    /// the matching `.trashinfo` file, inside `<trash>/info`
+    pub info: PathBuf,
+    /// DeletionDate as written in the info file (YYYY-MM-DDThh:mm:ss, local time)
+    pub deletion_date: String,
+}
+
+impl TrashEntry {
+    pub fn name(&self) -> String {
+        self.original
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+}
+
+/// The trash directories of the current user, `$XDG_DATA_HOME/Trash` is the home trash
+/// unless another one is given.
+#[derive(Clone)]
+pub struct Trash {
+    //None if there is no home directory
+    home: Option<PathBuf>,
+}
+
+impl Trash {
+    /// a trash whose home trash is `home`, e.g. inside a temporary directory.
+    /// Nothing can be trashed without a home trash
+    pub fn new(home: Option<PathBuf>) -> Trash {
+        Trash { home }
+    }
+
+    /// the trash of the current user in `$XDG_DATA_HOME/Trash`
+    pub fn of_user() -> Trash {
+        Trash::new(util::xdg_dir("XDG_DATA_HOME", ".local/share").map(|data| data.join("Trash")))
+    }
+
+    /// move `path` into the matching trash directory and return the created entry
+    pub fn move_to_trash(&self, path: &Path) -> io::Result<TrashEntry> {
+        move_to_trash(self.home.as_deref(), path)
+    }
+
+    /// list the content of all trash directories of the current user.
+    /// Unreadable info files are reported as errors but don't stop the listing.
+    pub fn list(&self) -> (Vec<TrashEntry>, Vec<io::Error>) {
+        list(self.home.as_deref())
+    }
+}
+
+/// move a trashed item back to its original location
+pub fn restore(entry: &TrashEntry) -> io::Result<()> {
+    if entry.original.symlink_metadata().is_ok() {
+        return Err(Error::new(
+            ErrorKind::AlreadyExists,
+            format!(
+                "Cannot restore, {} already exists",
+                entry.original.display()
+            ),
+        ));
+    }
+    if let Some(parent) = entry.original.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::rename(&entry.file, &entry.original)?;
+    fs::remove_file(&entry.info)
+}
+
+/// permanently delete a trashed item
+pub fn purge(entry: &TrashEntry) -> io::Result<()> {
+    match entry.file.symlink_metadata() {
+        Ok(meta) if meta.is_dir() => fs::remove_dir_all(&entry.file)?,
+        Ok(_) => fs::remove_file(&entry.file)?,
+        Err(e) if e.kind() == ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    fs::remove_file(&entry.info)
+}
+
+#[cfg(not(unix))]
+fn move_to_trash(_home: Option<&Path>, path: &Path) -> io::Result<TrashEntry> {
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        format!(
+            "Cannot trash {}, there is no trash on this platform",
+            path.display()
+        ),
+    ))
+}
+
+#[cfg(not(unix))]
+fn list(_home: Option<&Path>) -> (Vec<TrashEntry>, Vec<io::Error>) {
+    (Vec::new(), Vec::new())
+}
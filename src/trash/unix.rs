@@ -0,0 +1,346 @@
+//! Implementation of the freedesktop.org Trash specification.
+//!
+//! Files on the same filesystem as the home trash are moved to `$XDG_DATA_HOME/Trash`,
+//! files on other filesystems go to the trash directory at the top of their mount point
+//! (`$topdir/.Trash/$uid` or `$topdir/.Trash-$uid`).
+
+use super::TrashEntry;
+use crate::util;
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Error, ErrorKind, Write};
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+const INFO_EXTENSION: &str = ".trashinfo";
+
+/// A trash directory and the directory that relative `Path=` keys are resolved against.
+struct TrashDir {
+    root: PathBuf,
+    top_dir: Option<PathBuf>,
+}
+
+impl TrashDir {
+    fn files(&self) -> PathBuf {
+        self.root.join("files")
+    }
+
+    fn info(&self) -> PathBuf {
+        self.root.join("info")
+    }
+
+    fn create_subdirs(&self) -> io::Result<()> {
+        let mut builder = fs::DirBuilder::new();
+        builder.recursive(true).mode(0o700);
+        builder.create(self.files())?;
+        builder.create(self.info())
+    }
+}
+
+/// move `path` into the trash directory for it, `home` is the home trash
+pub fn move_to_trash(home: Option<&Path>, path: &Path) -> io::Result<TrashEntry> {
+    let path = std::path::absolute(path)?;
+    let file_name = path.file_name().ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("Cannot trash {}", path.display()),
+        )
+    })?;
+    // the entry itself may be a symlink, the filesystem it lives on is the one of its parent
+    let parent = path.parent().unwrap_or(Path::new("/"));
+    fs::symlink_metadata(&path)?;
+    let device = fs::metadata(parent)?.dev();
+
+    let trash_dir = trash_dir_for(home, parent, device)?;
+    trash_dir.create_subdirs()?;
+
+    let stored_path = match &trash_dir.top_dir {
+        Some(top_dir) => path.strip_prefix(top_dir).unwrap_or(&path).to_path_buf(),
+        None => path.clone(),
+    };
+    let deletion_date = util::local_time(util::now_secs()).iso8601();
+    let info_content = format!(
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        percent_encode(stored_path.as_os_str().as_bytes()),
+        deletion_date
+    );
+
+    // reserve a unique name by atomically creating the info file first
+    let mut counter = 1;
+    let (info_path, file_path) = loop {
+        let mut name = file_name.to_os_string();
+        if counter > 1 {
+            name.push(format!(".{counter}"));
+        }
+        let mut info_name = name.clone();
+        info_name.push(INFO_EXTENSION);
+        let info_path = trash_dir.info().join(info_name);
+        let file_path = trash_dir.files().join(&name);
+
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&info_path)
+        {
+            Ok(mut info_file) => {
+                if file_path.symlink_metadata().is_ok() {
+                    let _ = fs::remove_file(&info_path);
+                } else {
+                    if let Err(e) = info_file.write_all(info_content.as_bytes()) {
+                        let _ = fs::remove_file(&info_path);
+                        return Err(e);
+                    }
+                    break (info_path, file_path);
+                }
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e),
+        }
+        counter += 1;
+    };
+
+    if let Err(e) = fs::rename(&path, &file_path) {
+        let _ = fs::remove_file(&info_path);
+        return Err(e);
+    }
+
+    Ok(TrashEntry {
+        original: path,
+        file: file_path,
+        info: info_path,
+        deletion_date,
+    })
+}
+
+/// list the content of the home trash and the trash directories of all mount points
+pub fn list(home: Option<&Path>) -> (Vec<TrashEntry>, Vec<io::Error>) {
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+
+    let mut trash_dirs: Vec<TrashDir> = Vec::new();
+    if let Some(home) = home {
+        trash_dirs.push(TrashDir {
+            root: home.to_path_buf(),
+            top_dir: None,
+        });
+    }
+    let uid = uid();
+    for mount_point in mount_points() {
+        for root in [
+            mount_point.join(".Trash").join(uid.to_string()),
+            mount_point.join(format!(".Trash-{uid}")),
+        ] {
+            if is_own_dir(&root) && !trash_dirs.iter().any(|dir| dir.root == root) {
+                trash_dirs.push(TrashDir {
+                    root,
+                    top_dir: Some(mount_point.clone()),
+                });
+            }
+        }
+    }
+
+    for trash_dir in trash_dirs {
+        let iter = match fs::read_dir(trash_dir.info()) {
+            Ok(iter) => iter,
+            Err(e) if e.kind() == ErrorKind::NotFound => continue,
+            Err(e) => {
+                errors.push(e);
+                continue;
+            }
+        };
+        for info_entry in iter.flatten() {
+            let info_name = info_entry.file_name();
+            let Some(name) = info_name.as_bytes().strip_suffix(INFO_EXTENSION.as_bytes()) else {
+                continue;
+            };
+            let file = trash_dir.files().join(OsString::from_vec(name.to_vec()));
+            if file.symlink_metadata().is_err() {
+                continue;
+            }
+            match parse_info(&info_entry.path()) {
+                Ok((path, deletion_date)) => {
+                    let original = match (&trash_dir.top_dir, path.is_absolute()) {
+                        (Some(top_dir), false) => top_dir.join(path),
+                        _ => path,
+                    };
+                    entries.push(TrashEntry {
+                        original,
+                        file,
+                        info: info_entry.path(),
+                        deletion_date,
+                    });
+                }
+                Err(e) => errors.push(e),
+            }
+        }
+    }
+
+    entries.sort_by(|a, b| b.deletion_date.cmp(&a.deletion_date));
+    (entries, errors)
+}
+
+fn parse_info(info_path: &Path) -> io::Result<(PathBuf, String)> {
+    let content = fs::read_to_string(info_path)?;
+    let mut path = None;
+    let mut deletion_date = String::new();
+    let mut in_section = false;
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_section = line == "[Trash Info]";
+        } else if in_section {
+            if let Some(value) = line.strip_prefix("Path=") {
+                path = Some(PathBuf::from(OsString::from_vec(percent_decode(value))));
+            } else if let Some(value) = line.strip_prefix("DeletionDate=") {
+                deletion_date = value.to_owned();
+            }
+        }
+    }
+    path.map(|path| (path, deletion_date)).ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Invalid trash info file: {}", info_path.display()),
+        )
+    })
+}
+
+/// pick the trash directory for a file whose parent directory lives on `device`
+fn trash_dir_for(home: Option<&Path>, parent: &Path, device: u64) -> io::Result<TrashDir> {
+    let home = home.ok_or_else(|| Error::new(ErrorKind::NotFound, "Cannot find home directory"))?;
+    fs::create_dir_all(home)?;
+    if fs::metadata(home)?.dev() == device {
+        return Ok(TrashDir {
+            root: home.to_path_buf(),
+            top_dir: None,
+        });
+    }
+
+    let top_dir = mount_point_of(parent, device);
+    let uid = uid();
+
+    // $topdir/.Trash must be a real directory with the sticky bit set, otherwise it is ignored
+    let admin_trash = top_dir.join(".Trash");
+    if let Ok(meta) = fs::symlink_metadata(&admin_trash)
+        && meta.is_dir()
+        && meta.permissions().mode() & 0o1000 != 0
+    {
+        let root = admin_trash.join(uid.to_string());
+        let _ = fs::DirBuilder::new().mode(0o700).create(&root);
+        if is_own_dir(&root) {
+            return Ok(TrashDir {
+                root,
+                top_dir: Some(top_dir),
+            });
+        }
+    }
+
+    let root = top_dir.join(format!(".Trash-{uid}"));
+    match fs::DirBuilder::new().mode(0o700).create(&root) {
+        Ok(()) => {}
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+        Err(e) => {
+            return Err(Error::new(
+                e.kind(),
+                format!("Cannot create trash directory {}: {}", root.display(), e),
+            ));
+        }
+    }
+    if !is_own_dir(&root) {
+        return Err(Error::new(
+            ErrorKind::PermissionDenied,
+            format!(
+                "Cannot use trash directory {}, it is not a directory of the current user",
+                root.display()
+            ),
+        ));
+    }
+    Ok(TrashDir {
+        root,
+        top_dir: Some(top_dir),
+    })
+}
+
+/// walk up from `path` as long as the parent is still on `device`
+fn mount_point_of(path: &Path, device: u64) -> PathBuf {
+    let mut top = path.to_path_buf();
+    while let Some(parent) = top.parent() {
+        match fs::metadata(parent) {
+            Ok(meta) if meta.dev() == device => top = parent.to_path_buf(),
+            _ => break,
+        }
+    }
+    top
+}
+
+fn mount_points() -> Vec<PathBuf> {
+    let content = fs::read_to_string("/proc/self/mounts").unwrap_or_default();
+    content
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .map(|mount_point| PathBuf::from(OsString::from_vec(unescape_mount_point(mount_point))))
+        .collect()
+}
+
+/// /proc/self/mounts escapes spaces, tabs, newlines and backslashes as octal sequences
+fn unescape_mount_point(s: &str) -> Vec<u8> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\'
+            && let Some(Ok(value)) = s
+                .get(i + 1..i + 4)
+                .map(|octal| u8::from_str_radix(octal, 8))
+        {
+            out.push(value);
+            i += 4;
+            continue;
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    out
+}
+
+/// the trash directories of a mount point must be real directories owned by the user,
+/// a symlink or a directory of another user could expose the trashed files
+fn is_own_dir(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok_and(|meta| meta.is_dir() && meta.uid() == uid())
+}
+
+fn uid() -> u32 {
+    // SAFETY: getuid has no preconditions and cannot fail
+    unsafe { libc::getuid() }
+}
+
+/// percent-encode everything except unreserved characters and '/'
+pub fn percent_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len());
+    for &byte in bytes {
+        if byte.is_ascii_alphanumeric() || b"-_.~/".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+pub fn percent_decode(s: &str) -> Vec<u8> {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && let Some(Ok(value)) = s.get(i + 1..i + 3).map(|hex| u8::from_str_radix(hex, 16))
+        {
+            decoded.push(value);
+            i += 3;
+            continue;
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    decoded
+}
@@ -0,0 +1,72 @@
+//! The terminal the app draws on. That is stdout, unless stdout is needed for the directory
+//! printed by `--print-cwd`: then the app draws on stderr so `$(pathexplorer --print-cwd)` works.
+
+use crossterm::execute;
+use crossterm::terminal::{
+    EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
+};
+use ratatui::Terminal;
+use ratatui::backend::CrosstermBackend;
+use std::io::{self, Write};
+use std::sync::OnceLock;
+
+pub type Tui = Terminal<CrosstermBackend<Output>>;
+
+//chosen once by init, like raw mode it is the same for the whole process
+static OUTPUT: OnceLock<Output> = OnceLock::new();
+
+#[derive(Copy, Clone)]
+pub enum Output {
+    Stdout,
+    Stderr,
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Output::Stdout => io::stdout().write(buf),
+            Output::Stderr => io::stderr().write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Output::Stdout => io::stdout().flush(),
+            Output::Stderr => io::stderr().flush(),
+        }
+    }
+}
+
+/// take over the terminal, it is restored before a panic message is printed
+pub fn init(output: Output) -> io::Result<Tui> {
+    let _ = OUTPUT.set(output);
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = restore();
+        hook(info);
+    }));
+    enter()
+}
+
+/// leave the alternate screen, e.g. while an external editor runs or before exiting
+pub fn restore() -> io::Result<()> {
+    //disabling raw mode first is important as it has more side effects
+    disable_raw_mode()?;
+    execute!(output(), LeaveAlternateScreen)
+}
+
+/// take over the terminal again after `restore`
+pub fn resume(terminal: &mut Tui) -> io::Result<()> {
+    *terminal = enter()?;
+    terminal.clear()
+}
+
+fn enter() -> io::Result<Tui> {
+    enable_raw_mode()?;
+    execute!(output(), EnterAlternateScreen)?;
+    Terminal::new(CrosstermBackend::new(output()))
+}
+
+fn output() -> Output {
+    OUTPUT.get().copied().unwrap_or(Output::Stdout)
+}
@@ -9,3 +9,173 @@ pub fn popup_area(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
     let [area] = horizontal.areas(area);
     area
 }
+
+/// size in bytes with a binary unit, e.g. 1.5 MiB, or with a decimal unit like 1.6 MB if `si`
+pub fn format_bytes(bytes: u64, si: bool) -> String {
+    let (base, units) = if si {
+        (1000.0, ["B", "kB", "MB", "GB", "TB", "PB"])
+    } else {
+        (1024.0, ["B", "KiB", "MiB", "GiB", "TiB", "PiB"])
+    };
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= base && unit < units.len() - 1 {
+        size /= base;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", units[unit])
+    }
+}
+
+/// duration as h:mm:ss or m:ss
+pub fn format_duration(duration: std::time::Duration) -> String {
+    let secs = duration.as_secs();
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
+
+/// seconds since the unix epoch
+pub fn now_secs() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+/// broken down local time
+pub struct LocalTime {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+}
+
+impl LocalTime {
+    /// YYYY-MM-DDThh:mm:ss
+    pub fn iso8601(&self) -> String {
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+/// convert seconds since the unix epoch into local time
+pub fn local_time(secs: i64) -> LocalTime {
+    let time = secs as libc::time_t;
+    // SAFETY: tm is a plain C struct for which all zero bytes are a valid value
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    // SAFETY: both pointers are valid for the duration of the call
+    unsafe { libc::localtime_r(&time, &mut tm) };
+    LocalTime {
+        year: tm.tm_year + 1900,
+        month: (tm.tm_mon + 1) as u32,
+        day: tm.tm_mday as u32,
+        hour: tm.tm_hour as u32,
+        minute: tm.tm_min as u32,
+        second: tm.tm_sec as u32,
+    }
+}
+
+/// resolve an XDG base directory: the value of `env_var` if it is an absolute path,
+/// otherwise `$HOME/<fallback>`
+pub fn xdg_dir(env_var: &str, fallback: &str) -> Option<std::path::PathBuf> {
+    match std::env::var_os(env_var).map(std::path::PathBuf::from) {
+        Some(path) if path.is_absolute() => Some(path),
+        _ => std::env::home_dir().map(|home| home.join(fallback)),
+    }
+}
+
+/// resolve `.` and `..` in an absolute path without following symlinks, like `cd` in a shell
+pub fn normalize_path(path: &std::path::Path) -> std::path::PathBuf {
+    use std::path::Component;
+
+    let mut normalized = std::path::PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// create a new file only the user can access in the temp directory. The file must not exist
+/// yet, so a symlink planted in a shared /tmp is never followed
+fn create_temp_file() -> std::io::Result<(std::path::PathBuf, std::fs::File)> {
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    let mut seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_nanos() as u64)
+        ^ u64::from(std::process::id()) << 32;
+    loop {
+        //xorshift, the name only has to be hard to guess in advance
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        let path = std::env::temp_dir().join(format!("pathexplorer-{seed:016x}.txt"));
+        match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&path)
+        {
+            Ok(file) => return Ok((path, file)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// write `lines` into a temporary file, open it in `$VISUAL`/`$EDITOR` (falls back to vi) and
+/// return the edited lines. The terminal has to be restored before calling this.
+pub fn edit_lines_in_editor(lines: &[String]) -> std::io::Result<Vec<String>> {
+    use std::io::Write;
+
+    let (temp_file, mut file) = create_temp_file()?;
+    let written = file.write_all((lines.join("\n") + "\n").as_bytes());
+    drop(file);
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&temp_file);
+        return Err(e);
+    }
+
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| String::from("vi"));
+    let mut editor_parts = editor.split_whitespace();
+    let program = editor_parts.next().unwrap_or("vi");
+
+    let status = std::process::Command::new(program)
+        .args(editor_parts)
+        .arg(&temp_file)
+        .status();
+    let content = std::fs::read_to_string(&temp_file);
+    let _ = std::fs::remove_file(&temp_file);
+
+    let status = status?;
+    if !status.success() {
+        return Err(std::io::Error::other(format!(
+            "{editor} exited with {status}"
+        )));
+    }
+    let mut edited: Vec<String> = content?.lines().map(String::from).collect();
+    while edited.last().is_some_and(|line| line.is_empty()) {
+        edited.pop();
+    }
+    Ok(edited)
+}
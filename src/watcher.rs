@@ -0,0 +1,110 @@
+//! Watches the listed directories for changes made by other processes.
+
+use notify::event::{AccessKind, AccessMode, MetadataKind, ModifyKind};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::io;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+
+/// a burst of events is reported once nothing happened for this long
+const DEBOUNCE: Duration = Duration::from_millis(250);
+/// constant changes are still reported this often
+const MAX_DELAY: Duration = Duration::from_secs(1);
+
+pub struct DirWatcher {
+    watcher: Option<RecommendedWatcher>,
+    events: Receiver<()>,
+    watched: Vec<PathBuf>,
+    //(first, last) event that was not reported yet
+    pending: Option<(Instant, Instant)>,
+}
+
+impl DirWatcher {
+    /// a watcher that does nothing if the platform has no file system notifications
+    pub fn new() -> (DirWatcher, Option<io::Error>) {
+        let (sender, receiver) = mpsc::channel();
+        let result = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            if let Ok(event) = event
+                && is_change(&event.kind)
+            {
+                let _ = sender.send(());
+            }
+        });
+        let (watcher, error) = match result {
+            Ok(watcher) => (Some(watcher), None),
+            Err(e) => (None, Some(to_io_error(e))),
+        };
+        let dir_watcher = DirWatcher {
+            watcher,
+            events: receiver,
+            watched: Vec::new(),
+            pending: None,
+        };
+        (dir_watcher, error)
+    }
+
+    /// watch `dirs` instead of the previous directories, e.g. the directories of all tabs.
+    /// Their subdirectories are not watched
+    pub fn watch(&mut self, dirs: &[PathBuf]) -> io::Result<()> {
+        if self.watched == dirs {
+            return Ok(());
+        }
+        let Some(watcher) = self.watcher.as_mut() else {
+            return Ok(());
+        };
+        for watched in &self.watched {
+            if !dirs.contains(watched) {
+                let _ = watcher.unwatch(watched);
+            }
+        }
+        //events of the old directories must not trigger a refresh of the new ones
+        while self.events.try_recv().is_ok() {}
+        self.pending = None;
+
+        //a failed watch is not retried until the directories change
+        let mut result = Ok(());
+        for dir in dirs {
+            if !self.watched.contains(dir)
+                && let Err(e) = watcher.watch(dir, RecursiveMode::NonRecursive)
+            {
+                result = result.and(Err(to_io_error(e)));
+            }
+        }
+        self.watched = dirs.to_vec();
+        result
+    }
+
+    /// returns true once after a burst of changes settled down
+    pub fn poll(&mut self) -> bool {
+        let now = Instant::now();
+        while self.events.try_recv().is_ok() {
+            let first = self.pending.map_or(now, |(first, _)| first);
+            self.pending = Some((first, now));
+        }
+        match self.pending {
+            Some((first, last)) if now - last >= DEBOUNCE || now - first >= MAX_DELAY => {
+                self.pending = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// reading files (e.g. for the preview) must not trigger a refresh
+fn is_change(kind: &EventKind) -> bool {
+    match kind {
+        EventKind::Access(AccessKind::Close(AccessMode::Write)) => true,
+        EventKind::Access(_) => false,
+        EventKind::Modify(ModifyKind::Metadata(MetadataKind::AccessTime)) => false,
+        _ => true,
+    }
+}
+
+fn to_io_error(error: notify::Error) -> io::Error {
+    match error.kind {
+        notify::ErrorKind::Io(e) => e,
+        _ => io::Error::other(error.to_string()),
+    }
+}
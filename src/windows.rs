@@ -1,2 +1,4 @@
+pub mod disk_usage_table;
 pub mod explorer_table;
 pub mod popups;
+pub mod trash_table;
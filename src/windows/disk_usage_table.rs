@@ -0,0 +1,344 @@
+use crate::controller::{AppEvents, State};
+use crate::disk_usage::{Scan, UsageNode};
+use crate::file_manager::FileManager;
+use crate::keymap::Action;
+use crate::message::{Message, MessageReceiver, MessageSender};
+use crate::util;
+use crossterm::event::KeyEvent;
+use ratatui::Frame;
+use ratatui::layout::{Alignment, Constraint};
+use ratatui::prelude::{Line, Style, Stylize};
+use ratatui::symbols::border;
+use ratatui::widgets::{Block, Paragraph, Row, Table, TableState};
+use std::path::PathBuf;
+
+const BAR_WIDTH: usize = 20;
+
+enum MessageSource {
+    None,
+    TrashConfirmationPrompt,
+    PermanentDeletionConfirmationPrompt,
+}
+
+///Window that scans a directory tree and lists the children of a folder by their cumulative
+///size, so it's easy to find out what takes up the disk space
+pub struct DiskUsageTable {
+    table_state: TableState,
+    root: PathBuf,
+    scan: Option<Scan>,
+    tree: Option<UsageNode>,
+    //child indices from the root to the shown folder
+    position: Vec<usize>,
+    message_source: MessageSource,
+    message: Option<Message>,
+    //paths removed from the tree whose trash or delete job may not be finished yet
+    deleting: Vec<PathBuf>,
+}
+
+impl DiskUsageTable {
+    pub fn new() -> DiskUsageTable {
+        DiskUsageTable {
+            table_state: TableState::new(),
+            root: PathBuf::new(),
+            scan: None,
+            tree: None,
+            position: Vec::new(),
+            message_source: MessageSource::None,
+            message: None,
+            deleting: Vec::new(),
+        }
+    }
+
+    fn start_scan(&mut self, root: PathBuf) {
+        self.root = root.clone();
+        self.scan = Some(Scan::start(root));
+        self.tree = None;
+        self.position.clear();
+        self.table_state.select_first();
+    }
+
+    fn poll_scan(&mut self) {
+        if let Some(tree) = self.scan.as_ref().and_then(|scan| scan.poll()) {
+            self.tree = Some(tree);
+            self.scan = None;
+        }
+    }
+
+    ///once the jobs are done, scan again if a removed path is still there, e.g. because
+    ///the deletion failed
+    fn poll_deletions(&mut self, file_manager: &FileManager) {
+        if self.deleting.is_empty() || file_manager.unfinished_jobs() > 0 {
+            return;
+        }
+        let failed = self
+            .deleting
+            .drain(..)
+            .any(|path| path.symlink_metadata().is_ok());
+        if failed {
+            self.start_scan(self.root.clone());
+        }
+    }
+
+    ///the folder whose children are shown
+    fn current_node(&self) -> Option<&UsageNode> {
+        self.tree.as_ref()?.descendant(&self.position)
+    }
+
+    fn current_path(&self) -> PathBuf {
+        let mut path = self.root.clone();
+        if let Some(tree) = &self.tree {
+            let mut node = tree;
+            for index in &self.position {
+                node = &node.children[*index];
+                path.push(&node.name);
+            }
+        }
+        path
+    }
+
+    fn selected_child(&self) -> Option<&UsageNode> {
+        self.current_node()?
+            .children
+            .get(self.table_state.selected()?)
+    }
+
+    fn selected_path(&self) -> Option<PathBuf> {
+        let child = self.selected_child()?;
+        Some(self.current_path().join(&child.name))
+    }
+
+    ///remove the selected child from the tree after it was deleted
+    fn remove_selected(&mut self) {
+        let (Some(tree), Some(selected)) = (self.tree.as_mut(), self.table_state.selected()) else {
+            return;
+        };
+        let mut position = self.position.clone();
+        position.push(selected);
+        tree.remove(&position);
+        let len = self.current_node().map_or(0, |node| node.children.len());
+        if selected >= len {
+            self.table_state.select(len.checked_sub(1));
+        }
+    }
+
+    ///drill down into the selected folder
+    fn open_selected(&mut self) {
+        if let Some(child) = self.selected_child()
+            && child.is_dir
+            && let Some(selected) = self.table_state.selected()
+        {
+            self.position.push(selected);
+            self.table_state.select_first();
+        }
+    }
+
+    ///ask for confirmation before the selected child gets deleted
+    fn confirm_deletion(&mut self, source: MessageSource) -> AppEvents {
+        let Some(path) = self.selected_path() else {
+            return AppEvents::None;
+        };
+        let text = match source {
+            MessageSource::PermanentDeletionConfirmationPrompt => {
+                "will be deleted PERMANENTLY and cannot be restored"
+            }
+            _ => "will be moved to the trash",
+        };
+        self.message = Some(Message::String(format!(
+            "\"{}\" {}, are you sure?",
+            path.display(),
+            text
+        )));
+        self.message_source = source;
+        AppEvents::OpenConfirmationPopup
+    }
+}
+
+impl MessageReceiver for DiskUsageTable {
+    fn handle_message(&mut self, message: Option<Message>, file_manager: &mut FileManager) {
+        let confirmed = matches!(message, Some(Message::Bool(true)));
+        match self.message_source {
+            MessageSource::TrashConfirmationPrompt if confirmed => {
+                if let Some(path) = self.selected_path()
+                    && file_manager.trash_paths(vec![path.clone()])
+                {
+                    self.remove_selected();
+                    self.deleting.push(path);
+                }
+            }
+            MessageSource::PermanentDeletionConfirmationPrompt if confirmed => {
+                if let Some(path) = self.selected_path()
+                    && file_manager.delete_paths_permanently(vec![path.clone()])
+                {
+                    self.remove_selected();
+                    self.deleting.push(path);
+                }
+            }
+            _ => {}
+        }
+        self.message_source = MessageSource::None;
+    }
+}
+
+impl MessageSender for DiskUsageTable {
+    fn get_message(&mut self) -> Option<Message> {
+        self.message.take()
+    }
+}
+
+impl State for DiskUsageTable {
+    ///the scan or the deletion of rows is not finished yet
+    fn is_busy(&self, _file_manager: &FileManager) -> bool {
+        self.scan.is_some() || !self.deleting.is_empty()
+    }
+
+    ///scan the current directory unless it was already scanned
+    fn enter(&mut self, file_manager: &mut FileManager) {
+        let current_dir = file_manager.current_dir();
+        if current_dir != self.root || (self.tree.is_none() && self.scan.is_none()) {
+            self.start_scan(current_dir);
+        }
+    }
+
+    fn handle_key_event(
+        &mut self,
+        key_event: KeyEvent,
+        file_manager: &mut FileManager,
+    ) -> AppEvents {
+        self.poll_scan();
+        let keymap = file_manager.keymap();
+        let pressed = |action| keymap.is_bound(&key_event, action);
+        match keymap.popup_action(&key_event) {
+            Some(Action::Close) => return AppEvents::ChangeToExplorerWindow,
+            Some(Action::Down) => {
+                let len = self.current_node().map_or(0, |node| node.children.len());
+                match self.table_state.selected() {
+                    Some(selected) if selected + 1 >= len => self.table_state.select_first(),
+                    _ => self.table_state.select_next(),
+                }
+            }
+            Some(Action::Up) => match self.table_state.selected() {
+                Some(0) | None => self.table_state.select_last(),
+                _ => self.table_state.select_previous(),
+            },
+            Some(Action::GoTop) => self.table_state.select_first(),
+            Some(Action::GoBottom) => self.table_state.select_last(),
+            Some(Action::Confirm) => self.open_selected(),
+            _ if pressed(Action::DiskUsage) => return AppEvents::ChangeToExplorerWindow,
+            _ if pressed(Action::EnterDir) => self.open_selected(),
+            //back to the parent folder, with the folder we came from selected
+            _ if pressed(Action::LeaveDir) => {
+                if let Some(index) = self.position.pop() {
+                    self.table_state.select(Some(index));
+                }
+            }
+            _ if pressed(Action::Trash) => {
+                return self.confirm_deletion(MessageSource::TrashConfirmationPrompt);
+            }
+            _ if pressed(Action::Delete) => {
+                return self.confirm_deletion(MessageSource::PermanentDeletionConfirmationPrompt);
+            }
+            _ if pressed(Action::Rescan) => self.start_scan(self.root.clone()),
+            _ => {}
+        }
+        AppEvents::None
+    }
+
+    fn draw(&mut self, frame: &mut Frame, file_manager: &mut FileManager) {
+        self.poll_scan();
+        self.poll_deletions(file_manager);
+        let si = file_manager.si_units;
+
+        let title = Line::from(format!("DISK USAGE {}", self.current_path().display())).bold();
+        let keymap = file_manager.keymap();
+        let help_text = Line::from(format!(
+            "open:{} up:{} trash:{} delete:{} rescan:{} back:{}",
+            keymap.hint(Action::EnterDir),
+            keymap.hint(Action::LeaveDir),
+            keymap.hint(Action::Trash),
+            keymap.hint(Action::Delete),
+            keymap.hint(Action::Rescan),
+            keymap.hint(Action::Close)
+        ));
+        let mut table_block = Block::bordered()
+            .title(title.left_aligned())
+            .border_set(border::THICK)
+            .title_bottom(help_text.right_aligned().bold());
+
+        if let Some(scan) = &self.scan {
+            let (files, bytes) = scan.progress();
+            let text = format!(
+                "scanning {} … {} files, {}",
+                scan.root().display(),
+                files,
+                util::format_bytes(bytes, si)
+            );
+            let paragraph = Paragraph::new(text)
+                .alignment(Alignment::Center)
+                .block(table_block);
+            frame.render_widget(paragraph, frame.area());
+            return;
+        }
+
+        let Some(node) = self.current_node() else {
+            frame.render_widget(table_block, frame.area());
+            return;
+        };
+        table_block = table_block.title(
+            Line::from(format!("total: {}", util::format_bytes(node.size, si)))
+                .right_aligned()
+                .bold(),
+        );
+
+        let header = Row::new(vec!["SIZE", "", "", "NAME"]).bold().dark_gray();
+        let rows: Vec<Row> = node
+            .children
+            .iter()
+            .map(|child| {
+                let fraction = if node.size == 0 {
+                    0.0
+                } else {
+                    child.size as f64 / node.size as f64
+                };
+                let name = child.name.to_string_lossy();
+                let row = Row::new(vec![
+                    util::format_bytes(child.size, si),
+                    format!("{:5.1}%", fraction * 100.0),
+                    usage_bar(fraction, BAR_WIDTH),
+                    if child.is_dir {
+                        format!("{name}/")
+                    } else {
+                        name.into_owned()
+                    },
+                ]);
+                if child.is_dir { row.blue() } else { row }
+            })
+            .collect();
+        let widths = [
+            Constraint::Length(10),
+            Constraint::Length(6),
+            Constraint::Length(BAR_WIDTH as u16 + 2),
+            Constraint::Fill(1),
+        ];
+
+        let table = Table::new(rows, widths)
+            .block(table_block)
+            .header(header)
+            .row_highlight_style(Style::new().green());
+
+        frame.render_stateful_widget(table, frame.area(), &mut self.table_state);
+    }
+}
+
+///a bar like [████▌     ] that is filled to `fraction`, `width` is the inner width
+fn usage_bar(fraction: f64, width: usize) -> String {
+    const PARTIAL: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+    let eighths = (fraction.clamp(0.0, 1.0) * (width * 8) as f64).round() as usize;
+    let mut bar = String::from("[");
+    bar.extend(std::iter::repeat_n('█', eighths / 8));
+    if eighths / 8 < width {
+        bar.push(PARTIAL[eighths % 8]);
+        bar.extend(std::iter::repeat_n(' ', width - eighths / 8 - 1));
+    }
+    bar.push(']');
+    bar
+}
@@ -1,39 +1,102 @@
+use crate::cli::PickMode;
+use crate::columns::{self, Column, NameCache};
+use crate::config::Config;
 use crate::controller::{AppEvents, State};
 use crate::file_manager::{FileManager, SortDir};
+use crate::filter::{Filter, FilterMode};
+use crate::jobs::{Job, JobStatus};
+use crate::keymap::{self, Action, Key, Lookup};
 use crate::message::{Message, MessageReceiver, MessageSender};
+use crate::preview::Previewer;
 use crate::string_ring_buffer::StringRingBuffer;
-use crossterm::event::{KeyCode, KeyEvent};
+use crate::util;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::Frame;
-use ratatui::layout::{Constraint, Layout};
-use ratatui::prelude::{Line, Style, Stylize};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::prelude::{Line, Span, Style, Stylize};
 use ratatui::symbols::border;
-use ratatui::widgets::{Block, Paragraph, Row, Table, TableState, Wrap};
-use std::path::PathBuf;
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Row, Table, TableState, Wrap};
+use std::collections::HashMap;
+use std::fs::DirEntry;
+use std::path::{Path, PathBuf};
+use unicode_width::UnicodeWidthStr;
+
+//columns at the end are hidden if the name column would get narrower than this
+const MIN_NAME_WIDTH: usize = 16;
+
+//what the letter after <m> or <'> does
+enum MarkAction {
+    Set,
+    Jump,
+}
 
 //this enum is used to know which part of the window requested the popup to properly handle the
 //message
 enum MessageSource {
     None,
     DeletionConfirmationPrompt,
+    PermanentDeletionConfirmationPrompt,
     PathChangePopup,
+    PasteConflictPopup(PathBuf),
+    FuzzyFinderPopup,
+    RecentDirsPopup,
+    BookmarksPopup,
+    RenamePopup(PathBuf),
+    BulkRenameEditor(Vec<PathBuf>),
+    BulkRenameConfirmationPrompt(Vec<(PathBuf, PathBuf)>),
 }
 
 pub struct ExplorerTable {
     table_state: TableState,
+    //the tabs shown side by side in the dual pane layout, the shown tab is one of them
+    panes: Option<[usize; 2]>,
     message_source: MessageSource,
     message: Option<Message>,
+    //numeric prefix typed before a command, e.g. <3u> undoes the last three operations
+    count: Option<usize>,
+    //<m> or <'> was pressed, the next key names the mark
+    pending_mark: Option<MarkAction>,
+    //the keys typed so far of a binding with several keys, e.g. <g> of <g g>
+    pending_keys: Vec<Key>,
+    //the jobs panel has the focus, the keys control the selected job
+    jobs_focused: bool,
+    jobs_state: ListState,
+    //the filter pattern is being typed, the listing narrows with every key
+    filter_editing: bool,
+    filter_text: String,
+    filter_mode: FilterMode,
+    filter_error: Option<String>,
+    //preview of the highlighted entry, None while the preview pane is hidden
+    previewer: Option<Previewer>,
+    //parent and child directory next to the listing, None while the listing is shown alone.
+    //Previews the highlighted file in place of the child directory
+    miller_columns: Option<Previewer>,
+    name_cache: NameCache,
 
     error_ring_buffer: StringRingBuffer,
 }
 
 impl ExplorerTable {
-    pub fn new() -> ExplorerTable {
+    pub fn new(config: &Config) -> ExplorerTable {
         let mut explorer_table = ExplorerTable {
             table_state: TableState::new(),
+            panes: None,
             message_source: MessageSource::None,
             message: None,
+            count: None,
+            pending_mark: None,
+            pending_keys: Vec::new(),
+            jobs_focused: false,
+            jobs_state: ListState::default(),
+            filter_editing: false,
+            filter_text: String::new(),
+            filter_mode: FilterMode::Substring,
+            filter_error: None,
+            previewer: None,
+            miller_columns: None,
+            name_cache: NameCache::new(),
 
-            error_ring_buffer: StringRingBuffer::with_capacity(20),
+            error_ring_buffer: StringRingBuffer::with_capacity(config.error_log_capacity),
         };
         explorer_table.table_state.select_first_column();
         explorer_table.table_state.select_first();
@@ -52,80 +115,468 @@ impl ExplorerTable {
         };
         Some(entry.path())
     }
-}
 
-impl MessageReceiver for ExplorerTable {
-    fn handle_message(
-        &mut self,
-        message: Option<Message>,
-        file_manager: &mut crate::file_manager::FileManager,
-    ) {
-        match self.message_source {
-            MessageSource::DeletionConfirmationPrompt => {
-                if let Some(Message::Bool(true)) = message {
-                    file_manager.delete_selection();
+    /// select the row of path in the table, if it is part of the listing
+    pub fn select_path(&mut self, path: &Path, file_manager: &FileManager) {
+        if let Some(index) = file_manager.index_of(path) {
+            self.table_state.select(Some(index));
+        }
+    }
+
+    /// show the tab at `index`, each tab keeps its own highlighted row
+    fn switch_tab(&mut self, index: usize, file_manager: &mut FileManager) {
+        let previous = file_manager.current_tab();
+        let selected = file_manager.switch_tab(index, self.table_state.selected());
+        self.table_state = TableState::new().with_selected(selected);
+
+        //the focused pane shows the new tab, unless it already is in the other pane
+        let current = file_manager.current_tab();
+        if let Some(panes) = &mut self.panes
+            && !panes.contains(&current)
+        {
+            for pane in panes.iter_mut().filter(|pane| **pane == previous) {
+                *pane = current;
+            }
+        }
+    }
+
+    fn open_tab(&mut self, file_manager: &mut FileManager) {
+        let previous = file_manager.current_tab();
+        let selected = file_manager.open_tab(self.table_state.selected());
+        self.table_state = TableState::new().with_selected(selected);
+        let current = file_manager.current_tab();
+
+        //the new tab replaces the one of the focused pane
+        if let Some(panes) = &mut self.panes {
+            for pane in panes.iter_mut() {
+                if *pane == previous {
+                    *pane = current;
+                } else if *pane >= current {
+                    *pane += 1;
                 }
             }
-            MessageSource::PathChangePopup => {
-                if let Some(Message::String(path_string)) = message {
-                    let new_path = PathBuf::from(path_string);
+        }
+    }
 
-                    file_manager.change_dir_with_error_handling(new_path);
+    fn close_tab(&mut self, file_manager: &mut FileManager) {
+        if file_manager.tab_count() <= 1 {
+            return;
+        }
+        let closed = file_manager.current_tab();
+        let selected = file_manager.close_tab(self.table_state.selected());
+        self.table_state = TableState::new().with_selected(selected);
 
-                    if self.table_state.selected().is_none() {
-                        self.table_state.select(Some(0));
-                    }
+        //the focused pane shows the neighbour, the layout is left if that is the other pane
+        let current = file_manager.current_tab();
+        if let Some(panes) = &mut self.panes {
+            for pane in panes.iter_mut() {
+                if *pane == closed {
+                    *pane = current;
+                } else if *pane > closed {
+                    *pane -= 1;
                 }
-                self.message_source = MessageSource::None;
             }
-            MessageSource::None => {}
+            if panes[0] == panes[1] {
+                self.panes = None;
+            }
         }
     }
-}
-impl MessageSender for ExplorerTable {
-    fn get_message(&mut self) -> Option<Message> {
-        self.message.take()
+
+    /// the tab of the pane that does not have the focus
+    fn other_pane(&self, file_manager: &FileManager) -> Option<usize> {
+        self.panes?
+            .into_iter()
+            .find(|pane| *pane != file_manager.current_tab())
     }
-}
 
-impl State for ExplorerTable {
-    fn enter(&mut self, file_manager: &mut FileManager) {
-        file_manager.update();
+    /// show the shown tab next to another one, a second tab is opened if there is none
+    fn toggle_dual_pane(&mut self, file_manager: &mut FileManager) {
+        if self.panes.take().is_some() {
+            return;
+        }
+        self.miller_columns = None;
+        if file_manager.tab_count() == 1 {
+            self.open_tab(file_manager);
+            self.switch_tab(0, file_manager);
+        }
+        let current = file_manager.current_tab();
+        let other = (current + 1) % file_manager.tab_count();
+        self.panes = Some([current.min(other), current.max(other)]);
     }
 
-    fn exit(&mut self, _file_manager: &mut FileManager) {}
+    /// pasted files go to the other pane, or to the current directory without a second pane
+    fn paste_target(&self, file_manager: &FileManager) -> PathBuf {
+        match self.other_pane(file_manager) {
+            Some(pane) => file_manager.tab_dirs().swap_remove(pane),
+            None => file_manager.current_dir(),
+        }
+    }
 
-    fn handle_key_event(
+    /// the numbered tabs with the names of their directories, the shown one highlighted
+    fn tab_bar(file_manager: &FileManager) -> Line<'static> {
+        let spans: Vec<Span> = file_manager
+            .tab_dirs()
+            .iter()
+            .enumerate()
+            .map(|(index, dir)| {
+                let name = dir
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| dir.display().to_string());
+                let span = Span::from(format!(" {}:{} ", index + 1, name));
+                if index == file_manager.current_tab() {
+                    span.reversed().bold()
+                } else {
+                    span
+                }
+            })
+            .collect();
+        Line::from(spans)
+    }
+
+    /// the table of `entries`, the widths of the columns depend on their content
+    fn table<'a>(
         &mut self,
-        key_event: KeyEvent,
+        entries: &[DirEntry],
+        depths: &[usize],
+        file_manager: &FileManager,
+        width: u16,
+        block: Block<'a>,
+    ) -> Table<'a> {
+        let columns = file_manager.columns.clone();
+        let mut natural_widths: Vec<usize> =
+            columns.iter().map(|column| column.header().len()).collect();
+        //the tree view indents the names
+        let mut guides = columns::tree_guides(depths).into_iter();
+        let mut cells: Vec<(Vec<String>, bool)> = Vec::new();
+        for entry in entries {
+            let meta = entry.metadata().ok();
+            let is_dir = meta.as_ref().is_some_and(|meta| meta.is_dir());
+            let mut guide = guides.next().unwrap_or_default();
+            if !depths.is_empty() {
+                guide.push_str(match is_dir {
+                    true if file_manager.is_expanded(&entry.path()) => "▾ ",
+                    true => "▸ ",
+                    false => "  ",
+                });
+            }
+            let row_strings: Vec<String> = columns
+                .iter()
+                .map(|column| {
+                    let value =
+                        column.value(entry, meta.as_ref(), file_manager, &mut self.name_cache);
+                    if *column == Column::Name {
+                        format!("{guide}{value}")
+                    } else {
+                        value
+                    }
+                })
+                .collect();
+            for (width, cell) in natural_widths.iter_mut().zip(&row_strings) {
+                *width = (*width).max(cell.width());
+            }
+            cells.push((row_strings, is_dir));
+        }
+        let available = width.saturating_sub(2) as usize;
+        let column_widths: Vec<(Column, usize)> = columns::layout_widths(
+            &columns
+                .iter()
+                .copied()
+                .zip(natural_widths)
+                .collect::<Vec<_>>(),
+            available,
+            MIN_NAME_WIDTH,
+        );
+
+        let header = Row::new(column_widths.iter().map(|(column, _)| column.header()))
+            .bold()
+            .dark_gray();
+        let mut rows: Vec<Row> = Vec::new();
+        for (entry, (row_strings, is_dir)) in entries.iter().zip(cells) {
+            let row_cells = column_widths.iter().map(|(column, width)| {
+                let index = columns.iter().position(|c| c == column).unwrap_or_default();
+                columns::elide(&row_strings[index], *width)
+            });
+            let mut row = Row::new(row_cells);
+            if file_manager.is_selected(&entry.path()) {
+                row = row.on_dark_gray();
+                if file_manager.is_cut() {
+                    row = row.italic();
+                }
+            } else if is_dir {
+                row = row.blue();
+            }
+            rows.push(row);
+        }
+        let widths = column_widths
+            .iter()
+            .map(|(_, width)| Constraint::Length(*width as u16));
+
+        Table::new(rows, widths)
+            .block(block)
+            .header(header)
+            .cell_highlight_style(Style::new().green())
+    }
+
+    /// the preview of `path` once the previewer loaded it
+    fn preview<'a>(
+        previewer: &mut Previewer,
+        path: Option<&Path>,
+        mut block: Block<'a>,
+    ) -> Paragraph<'a> {
+        let lines = match path {
+            None => Vec::new(),
+            Some(path) => {
+                previewer.request(path);
+                match previewer.current() {
+                    None => vec![Line::from("loading…").dark_gray()],
+                    Some(preview) => {
+                        if preview.truncated {
+                            block = block.title_bottom(Line::from("truncated").right_aligned());
+                        }
+                        preview.lines.clone()
+                    }
+                }
+            }
+        };
+        Paragraph::new(lines).block(block)
+    }
+
+    /// the parent directory with the current one highlighted, the listing and the highlighted
+    /// directory (or a preview of the highlighted file) side by side
+    fn draw_miller_columns(
+        &mut self,
+        frame: &mut Frame,
         file_manager: &mut FileManager,
-    ) -> AppEvents {
+        area: Rect,
+        block: Block,
+        highlighted: Option<PathBuf>,
+    ) {
+        let columns = Layout::horizontal([
+            Constraint::Percentage(20),
+            Constraint::Percentage(40),
+            Constraint::Percentage(40),
+        ])
+        .split(area);
+        let title = |dir: &Path| {
+            dir.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| dir.display().to_string())
+        };
+
+        let current_dir = file_manager.current_dir();
+        if let Some(parent) = current_dir.parent() {
+            file_manager.load_listing(parent);
+            let entries = file_manager.listing(parent);
+            let index = entries.iter().position(|entry| entry.path() == current_dir);
+            let mut table_state = TableState::new().with_selected_cell(index.map(|i| (i, 0)));
+            let parent_block = Block::bordered().title(title(parent));
+            let table = self.table(entries, &[], file_manager, columns[0].width, parent_block);
+            frame.render_stateful_widget(table, columns[0], &mut table_state);
+        }
+
+        let table = self.table(
+            file_manager.get_entries(),
+            file_manager.tree_depths(),
+            file_manager,
+            columns[1].width,
+            block,
+        );
+        frame.render_stateful_widget(table, columns[1], &mut self.table_state);
+
+        match highlighted {
+            Some(dir) if dir.is_dir() => {
+                file_manager.load_listing(&dir);
+                let child_block = Block::bordered().title(title(&dir));
+                let table = self.table(
+                    file_manager.listing(&dir),
+                    &[],
+                    file_manager,
+                    columns[2].width,
+                    child_block,
+                );
+                frame.render_widget(table, columns[2]);
+            }
+            file => {
+                if let Some(previewer) = &mut self.miller_columns {
+                    let preview =
+                        ExplorerTable::preview(previewer, file.as_deref(), Block::bordered());
+                    frame.render_widget(preview, columns[2]);
+                }
+            }
+        }
+    }
+
+    /// lines for the bulk rename editor, paths inside the cwd are written relative to it
+    fn bulk_rename_lines(sources: &[PathBuf], file_manager: &FileManager) -> Vec<String> {
+        let current_dir = file_manager.current_dir();
+        sources
+            .iter()
+            .map(|src| {
+                src.strip_prefix(&current_dir)
+                    .unwrap_or(src)
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect()
+    }
+
+    /// apply the typed filter, an invalid pattern keeps the previous filter
+    fn update_filter(&mut self, file_manager: &mut FileManager) {
+        if self.filter_text.is_empty() {
+            self.filter_error = None;
+            file_manager.set_filter(None);
+        } else {
+            match Filter::new(&self.filter_text, self.filter_mode) {
+                Ok(filter) => {
+                    self.filter_error = None;
+                    file_manager.set_filter(Some(filter));
+                }
+                Err(e) => {
+                    self.filter_error = Some(e);
+                    return;
+                }
+            }
+        }
+        self.table_state.select_first();
+    }
+
+    /// keys while the filter pattern is typed
+    fn handle_filter_key_event(&mut self, key_event: KeyEvent, file_manager: &mut FileManager) {
+        let action = if keymap::is_text(&key_event) {
+            None
+        } else {
+            file_manager.keymap().popup_action(&key_event)
+        };
         match key_event.code {
-            KeyCode::Char('q') => return AppEvents::Exit,
-            KeyCode::Char('s') => {
-                return AppEvents::OpenSortingPopupWindow;
+            _ if action == Some(Action::Confirm) => {
+                self.filter_editing = false;
+                return;
             }
-            KeyCode::Char('m') => {
-                return AppEvents::OpenKeyMappingPopupWindow;
+            _ if action == Some(Action::Close) => {
+                self.filter_editing = false;
+                self.filter_text.clear();
             }
-            KeyCode::Char('n') => {
-                return AppEvents::OpenNewFilePopup;
+            _ if !keymap::is_text(&key_event)
+                && file_manager
+                    .keymap()
+                    .is_bound(&key_event, Action::FilterMode) =>
+            {
+                self.filter_mode = self.filter_mode.next()
             }
-            KeyCode::Char('d') => {
-                match file_manager.dir_sorting {
-                    SortDir::Unsorted => file_manager.dir_sorting = SortDir::Start,
-                    SortDir::Start => file_manager.dir_sorting = SortDir::End,
-                    SortDir::End => file_manager.dir_sorting = SortDir::Unsorted,
+            KeyCode::Char(c) => self.filter_text.push(c),
+            KeyCode::Backspace if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.filter_text.clear()
+            }
+            KeyCode::Backspace => {
+                self.filter_text.pop();
+            }
+            _ => return,
+        }
+        self.update_filter(file_manager);
+    }
+
+    /// move the highlight `steps` matches forward (or backward), wrapping around at the ends
+    fn jump_to_match(&mut self, steps: isize, file_manager: &FileManager) {
+        let num_files = file_manager.num_files as isize;
+        if num_files == 0 {
+            return;
+        }
+        let selected = self.table_state.selected().unwrap_or_default() as isize;
+        let next = (selected + steps).rem_euclid(num_files);
+        self.table_state.select(Some(next as usize));
+    }
+
+    /// keys while the jobs panel is focused
+    fn handle_jobs_key_event(&mut self, key_event: KeyEvent, file_manager: &mut FileManager) {
+        let keymap = file_manager.keymap();
+        match keymap.popup_action(&key_event) {
+            Some(Action::Down) => self.jobs_state.select_next(),
+            Some(Action::Up) => self.jobs_state.select_previous(),
+            Some(Action::GoTop) => self.jobs_state.select_first(),
+            Some(Action::GoBottom) => self.jobs_state.select_last(),
+            Some(Action::Close) => self.jobs_focused = false,
+            _ if keymap.is_bound(&key_event, Action::FocusJobs) => self.jobs_focused = false,
+            _ if keymap.is_bound(&key_event, Action::PauseJob) => {
+                if let Some(index) = self.jobs_state.selected() {
+                    file_manager.toggle_pause_job(index);
                 }
-                file_manager.update();
-                return AppEvents::None;
             }
-            KeyCode::Down | KeyCode::Char('j') => {
+            _ if keymap.is_bound(&key_event, Action::CancelJob) => {
+                if let Some(index) = self.jobs_state.selected() {
+                    file_manager.cancel_job(index);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn job_item(job: &Job, si: bool) -> ListItem<'_> {
+        let progress = &job.progress;
+        let mut details = format!(
+            "{} {:>3.0}%  {}/{}  {}/{} files",
+            job.status.label(),
+            job.ratio() * 100.0,
+            util::format_bytes(progress.bytes_done, si),
+            util::format_bytes(progress.bytes_total, si),
+            progress.files_done,
+            progress.files_total,
+        );
+        if job.is_active() {
+            details.push_str(&format!(
+                "  {}/s",
+                util::format_bytes(job.throughput() as u64, si)
+            ));
+            if let Some(eta) = job.eta() {
+                details.push_str(&format!("  ETA {}", util::format_duration(eta)));
+            }
+        }
+        let details = match job.status {
+            JobStatus::Running => Line::from(details),
+            JobStatus::Paused | JobStatus::Cancelling => Line::from(details).yellow(),
+            JobStatus::Finished => Line::from(details).green(),
+            JobStatus::Cancelled => Line::from(details).dark_gray(),
+        };
+        ListItem::new(vec![Line::from(job.description.as_str()).bold(), details])
+    }
+
+    fn describe_renames(plan: &[(PathBuf, PathBuf)]) -> String {
+        const MAX_LINES: usize = 8;
+        let mut text = format!("Rename {} item(s)?\n", plan.len());
+        for (from, to) in plan.iter().take(MAX_LINES) {
+            let from_name = from.file_name().unwrap_or_default().to_string_lossy();
+            text.push_str(&format!("{} → {}\n", from_name, to.display()));
+        }
+        if plan.len() > MAX_LINES {
+            text.push_str(&format!("… and {} more", plan.len() - MAX_LINES));
+        }
+        text
+    }
+    /// run `action`, None if it doesn't apply right now, e.g. <n> without a filter.
+    /// The next action bound to the same keys is tried then
+    fn run_action(
+        &mut self,
+        action: Action,
+        count: Option<usize>,
+        file_manager: &mut FileManager,
+    ) -> Option<AppEvents> {
+        //refuse before a popup asks for details that can't be used
+        if action.changes_files() && !file_manager.check_writable() {
+            return Some(AppEvents::None);
+        }
+        let tab_count = file_manager.tab_count();
+        match action {
+            Action::Quit => return Some(AppEvents::Exit),
+            Action::Help => {
+                self.message = Some(Message::StringList(file_manager.keymap().help_lines()));
+                return Some(AppEvents::OpenKeyMappingPopupWindow);
+            }
+            Action::Down => {
                 let selected = self.table_state.selected();
                 match selected {
                     None => self.table_state.select_last(),
                     Some(selected) => {
-                        if file_manager.num_files - 1 <= selected {
+                        if selected + 1 >= file_manager.num_files {
                             self.table_state.select_first();
                         } else {
                             self.table_state.select_next();
@@ -133,7 +584,7 @@ impl State for ExplorerTable {
                     }
                 }
             }
-            KeyCode::Up | KeyCode::Char('k') => {
+            Action::Up => {
                 let selected = self.table_state.selected();
                 match selected {
                     None => self.table_state.select_last(),
@@ -146,83 +597,488 @@ impl State for ExplorerTable {
                     }
                 }
             }
+            Action::GoTop => self.table_state.select_first(),
+            Action::GoBottom => {
+                if file_manager.num_files > 0 {
+                    self.table_state.select(Some(file_manager.num_files - 1));
+                }
+            }
+            //the tree view expands the directory in place
+            Action::EnterDir if file_manager.is_tree_view() => {
+                if let Some(path) = self.selected_file_in_table(file_manager)
+                    && path.is_dir()
+                {
+                    file_manager.toggle_expanded(&path);
+                }
+            }
             //Enter selected Directory
-            KeyCode::Right | KeyCode::Char('l') => {
-                let index = match self.table_state.selected() {
-                    Some(index) => index,
-                    None => return AppEvents::None,
-                };
-                let entry = file_manager.get_entries().get(index).unwrap();
+            Action::EnterDir => {
+                let index = self.table_state.selected()?;
+                let entry = file_manager.get_entries().get(index)?;
                 file_manager.change_dir_with_error_handling(entry.path());
             }
+            //collapse the directory the highlighted entry is in
+            Action::LeaveDir
+                if self
+                    .table_state
+                    .selected()
+                    .and_then(|index| file_manager.tree_depths().get(index))
+                    .is_some_and(|depth| *depth > 0) =>
+            {
+                if let Some(path) = self.selected_file_in_table(file_manager)
+                    && let Some(parent) = path.parent()
+                {
+                    file_manager.toggle_expanded(parent);
+                    self.select_path(parent, file_manager);
+                }
+            }
             //Go to parent directory
-            KeyCode::Left | KeyCode::Char('h') => {
+            Action::LeaveDir => {
                 file_manager.change_dir_with_error_handling(PathBuf::from(".."));
                 if self.table_state.selected().is_none() {
                     self.table_state.select(Some(0));
                 }
             }
+            //the picker mode chooses instead of opening, the files mode still enters folders
+            Action::Pick => {
+                let mode = file_manager.pick_mode()?;
+                let highlighted = self.selected_file_in_table(file_manager);
+                let mut paths = file_manager.get_selection();
+                if !file_manager.picks_multiple() || paths.is_empty() {
+                    paths = match (mode, highlighted) {
+                        (PickMode::Files, Some(path)) if path.is_dir() => return None,
+                        (PickMode::Dirs, path) if !path.as_ref().is_some_and(|p| p.is_dir()) => {
+                            vec![file_manager.current_dir()]
+                        }
+                        (_, Some(path)) => vec![path],
+                        (_, None) => return Some(AppEvents::None),
+                    };
+                }
+                paths.sort();
+                if file_manager.pick(paths) {
+                    return Some(AppEvents::Exit);
+                }
+            }
+            //quitting without a pick cancels
+            Action::CancelPick => {
+                file_manager.pick_mode()?;
+                return Some(AppEvents::Exit);
+            }
+            Action::Open => {
+                let path = self.selected_file_in_table(file_manager)?;
+                file_manager.open_path(&path);
+            }
+            //browse back and forth through the visited directories
+            Action::GoBack => {
+                file_manager.go_back();
+                self.table_state.select_first();
+            }
+            Action::GoForward => {
+                file_manager.go_forward();
+                self.table_state.select_first();
+            }
+            Action::ChangePath => {
+                // Get current directory path
+                let current_path = file_manager
+                    .current_dir()
+                    .into_os_string()
+                    .into_string()
+                    .unwrap_or_default();
 
-            //toggle file/folder selection
-            KeyCode::Char('y') => {
-                let path = match self.selected_file_in_table(file_manager) {
-                    None => return AppEvents::None,
-                    Some(path) => path,
+                // Set message source and message
+                self.message_source = MessageSource::PathChangePopup;
+                self.message = Some(Message::TwoStrings(
+                    String::from("Change Path"),
+                    current_path,
+                ));
+
+                return Some(AppEvents::OpenTextFieldPopup);
+            }
+            //find a file below the current directory
+            Action::FindFile => {
+                self.message_source = MessageSource::FuzzyFinderPopup;
+                return Some(AppEvents::OpenFuzzyFinderPopup);
+            }
+            //jump to a recently visited directory
+            Action::RecentDirs => {
+                self.message_source = MessageSource::RecentDirsPopup;
+                return Some(AppEvents::OpenRecentDirsPopup);
+            }
+            //set or jump to a mark, the next key names it
+            Action::SetMark => self.pending_mark = Some(MarkAction::Set),
+            Action::JumpToMark => self.pending_mark = Some(MarkAction::Jump),
+            Action::Bookmarks => {
+                self.message_source = MessageSource::BookmarksPopup;
+                return Some(AppEvents::OpenBookmarksPopup);
+            }
+            //tabs
+            Action::OpenTab => self.open_tab(file_manager),
+            Action::CloseTab => self.close_tab(file_manager),
+            Action::NextTab => {
+                let index = (file_manager.current_tab() + 1) % tab_count;
+                self.switch_tab(index, file_manager);
+            }
+            Action::PreviousTab => {
+                let index = (file_manager.current_tab() + tab_count - 1) % tab_count;
+                self.switch_tab(index, file_manager);
+            }
+            Action::Tab(index) => self.switch_tab(index, file_manager),
+            //dual pane layout
+            Action::DualPane => self.toggle_dual_pane(file_manager),
+            Action::SwitchPane => {
+                if let Some(pane) = self.other_pane(file_manager) {
+                    self.switch_tab(pane, file_manager);
+                }
+            }
+            //miller columns layout
+            Action::MillerColumns => {
+                self.miller_columns = match self.miller_columns {
+                    Some(_) => None,
+                    None => Some(Previewer::new()),
+                };
+                self.panes = None;
+            }
+            Action::TreeView => {
+                file_manager.toggle_tree_view();
+                self.files_changed(file_manager);
+            }
+            //toggle the preview pane
+            Action::TogglePreview => {
+                self.previewer = match self.previewer {
+                    Some(_) => None,
+                    None => Some(Previewer::new()),
                 };
+            }
+            //filter the listing
+            Action::Filter => {
+                self.filter_editing = true;
+                if let Some(filter) = file_manager.filter() {
+                    self.filter_text = filter.pattern().to_owned();
+                    self.filter_mode = filter.mode();
+                } else {
+                    self.filter_text.clear();
+                }
+            }
+            Action::ClearFilter => {
+                file_manager.filter()?;
+                self.filter_text.clear();
+                self.update_filter(file_manager);
+            }
+            //jump between the matches of the filter
+            Action::NextMatch => {
+                file_manager.filter()?;
+                self.jump_to_match(count.unwrap_or(1) as isize, file_manager);
+            }
+            Action::PreviousMatch => {
+                file_manager.filter()?;
+                self.jump_to_match(-(count.unwrap_or(1) as isize), file_manager);
+            }
+            //toggle file/folder selection
+            Action::ToggleSelection => {
+                let path = self.selected_file_in_table(file_manager)?;
                 if file_manager.is_selected(&path) {
                     file_manager.remove_from_selection(path);
                 } else {
                     file_manager.add_to_selection(path);
                 }
             }
-
-            //clear selection
-            KeyCode::Char('c') => {
-                file_manager.clear_selection();
-            }
-            //paste selection
-            KeyCode::Char('v') => {
-                file_manager.paste();
-                file_manager.clear_selection();
+            Action::ClearSelection => file_manager.clear_selection(),
+            //cut selection, the next paste moves the files
+            Action::Cut => file_manager.toggle_cut(),
+            Action::Paste => {
+                let target = self.paste_target(file_manager);
+                let conflicts = file_manager.paste_conflicts(&target);
+                if conflicts.is_empty() {
+                    file_manager.paste(&target, &HashMap::new());
+                    file_manager.clear_selection();
+                } else {
+                    self.message_source = MessageSource::PasteConflictPopup(target.clone());
+                    self.message = Some(Message::PasteConflicts(target, conflicts));
+                    return Some(AppEvents::OpenConflictPopup);
+                }
             }
-
-            //delete selection
-            KeyCode::Char('x') => {
+            //move selection to trash
+            Action::Trash => {
                 self.message_source = MessageSource::DeletionConfirmationPrompt;
                 self.message = Some(Message::String(
-                    "The selected files will be deleted permanently, are you sure?".to_owned(),
+                    "The selected files will be moved to the trash, are you sure?".to_owned(),
                 ));
-                return AppEvents::OpenConfirmationPopup;
+                return Some(AppEvents::OpenConfirmationPopup);
             }
-            KeyCode::Char('g') => {
+            //delete selection permanently
+            Action::Delete => {
+                self.message_source = MessageSource::PermanentDeletionConfirmationPrompt;
+                self.message = Some(Message::String(
+                    "The selected files will be deleted PERMANENTLY and cannot be restored, are you sure?"
+                        .to_owned(),
+                ));
+                return Some(AppEvents::OpenConfirmationPopup);
+            }
+            //rename the highlighted file
+            Action::Rename => {
+                let path = self.selected_file_in_table(file_manager)?;
+                let name = path
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .into_owned();
+                self.message_source = MessageSource::RenamePopup(path);
+                self.message = Some(Message::TwoStrings(String::from("New"), name));
+                return Some(AppEvents::OpenTextFieldPopup);
+            }
+            //rename the selection (or the whole directory) in $EDITOR
+            Action::BulkRename => {
+                let mut sources = file_manager.get_selection();
+                if sources.is_empty() {
+                    sources = file_manager
+                        .get_entries()
+                        .iter()
+                        .map(DirEntry::path)
+                        .collect();
+                }
+                if sources.is_empty() {
+                    return Some(AppEvents::None);
+                }
+                sources.sort();
+                self.message = Some(Message::StringList(ExplorerTable::bulk_rename_lines(
+                    &sources,
+                    file_manager,
+                )));
+                self.message_source = MessageSource::BulkRenameEditor(sources);
+                return Some(AppEvents::OpenExternalEditor);
+            }
+            Action::NewFile => return Some(AppEvents::OpenNewFilePopup),
+            Action::Undo => file_manager.undo(count.unwrap_or(1)),
+            Action::Redo => file_manager.redo(count.unwrap_or(1)),
+            Action::History => return Some(AppEvents::OpenHistoryPopup),
+            //focus the jobs panel
+            Action::FocusJobs => {
+                if file_manager.jobs().is_empty() {
+                    return None;
+                }
+                self.jobs_focused = true;
+                if self.jobs_state.selected().is_none() {
+                    self.jobs_state.select_first();
+                }
+            }
+            Action::Sorting => return Some(AppEvents::OpenSortingPopupWindow),
+            Action::DirPositions => {
+                match file_manager.dir_sorting {
+                    SortDir::Unsorted => file_manager.dir_sorting = SortDir::Start,
+                    SortDir::Start => file_manager.dir_sorting = SortDir::End,
+                    SortDir::End => file_manager.dir_sorting = SortDir::Unsorted,
+                }
+                file_manager.update();
+                file_manager.save_preferences();
+            }
+            Action::Columns => return Some(AppEvents::OpenColumnsPopup),
+            //calculate the sizes of directories
+            Action::DirSizes => file_manager.toggle_dir_sizes(),
+            Action::ToggleHidden => {
                 file_manager.show_hidden = !file_manager.show_hidden;
                 file_manager.update();
+                file_manager.save_preferences();
             }
-            KeyCode::Tab => {
-                // Get current directory path
-                let current_path = match file_manager.current_dir() {
-                    Ok(path) => path.into_os_string().into_string().unwrap_or_default(),
-                    Err(_) => String::from(""),
-                };
+            Action::OpenTrash => return Some(AppEvents::ChangeToTrashWindow),
+            //analyze the disk usage below the current directory
+            Action::DiskUsage => return Some(AppEvents::ChangeToDiskUsageWindow),
+            //only used outside the explorer, lookup doesn't return them
+            Action::Confirm
+            | Action::Close
+            | Action::PauseJob
+            | Action::CancelJob
+            | Action::FilterMode
+            | Action::RenameMark
+            | Action::DeleteMark
+            | Action::ForgetDir
+            | Action::ToggleGitignore
+            | Action::ToggleColumn
+            | Action::MoveColumnUp
+            | Action::MoveColumnDown
+            | Action::ToggleUnits
+            | Action::ApplyToAll
+            | Action::Restore
+            | Action::Purge
+            | Action::Rescan => return None,
+        }
+        Some(AppEvents::None)
+    }
+}
 
-                // Set message source and message
-                self.message_source = MessageSource::PathChangePopup;
-                self.message = Some(Message::TwoStrings(
-                    String::from("Change Path"),
-                    current_path,
-                ));
+impl MessageReceiver for ExplorerTable {
+    fn handle_message(
+        &mut self,
+        message: Option<Message>,
+        file_manager: &mut crate::file_manager::FileManager,
+    ) {
+        match std::mem::replace(&mut self.message_source, MessageSource::None) {
+            MessageSource::DeletionConfirmationPrompt => {
+                if let Some(Message::Bool(true)) = message {
+                    file_manager.delete_selection();
+                }
+            }
+            MessageSource::PermanentDeletionConfirmationPrompt => {
+                if let Some(Message::Bool(true)) = message {
+                    file_manager.delete_selection_permanently();
+                }
+            }
+            MessageSource::PathChangePopup => {
+                if let Some(Message::String(path_string)) = message {
+                    let new_path = PathBuf::from(path_string);
 
-                return AppEvents::OpenTextFieldPopup;
+                    file_manager.change_dir_with_error_handling(new_path);
+
+                    if self.table_state.selected().is_none() {
+                        self.table_state.select(Some(0));
+                    }
+                }
             }
-            KeyCode::Enter => {
-                let path = match self.selected_file_in_table(file_manager) {
-                    None => return AppEvents::None,
-                    Some(path) => path,
-                };
-                file_manager.open_path(&path);
+            MessageSource::PasteConflictPopup(target) => {
+                if let Some(Message::ConflictResolutions(resolutions)) = message {
+                    file_manager.paste(&target, &resolutions);
+                    file_manager.clear_selection();
+                }
+            }
+            MessageSource::FuzzyFinderPopup => {
+                if let Some(Message::Paths(paths)) = message
+                    && let Some(path) = paths.first()
+                    && let Some(parent) = path.parent()
+                {
+                    file_manager.change_dir_with_error_handling(parent.to_path_buf());
+                    self.select_path(path, file_manager);
+                }
+            }
+            MessageSource::RecentDirsPopup | MessageSource::BookmarksPopup => {
+                if let Some(Message::Paths(paths)) = message
+                    && let Some(path) = paths.first()
+                {
+                    file_manager.change_dir_with_error_handling(path.clone());
+                    self.table_state.select_first();
+                }
+            }
+            MessageSource::RenamePopup(path) => {
+                if let Some(Message::String(new_name)) = message {
+                    file_manager.rename(&path, &new_name);
+                    self.select_path(&path.with_file_name(new_name), file_manager);
+                }
             }
+            MessageSource::BulkRenameEditor(sources) => {
+                if let Some(Message::StringList(new_names)) = message {
+                    match file_manager.plan_bulk_rename(&sources, &new_names) {
+                        Ok(plan) if plan.is_empty() => {}
+                        Ok(plan) => {
+                            self.message =
+                                Some(Message::String(ExplorerTable::describe_renames(&plan)));
+                            self.message_source = MessageSource::BulkRenameConfirmationPrompt(plan);
+                        }
+                        Err(e) => self.error_ring_buffer.push(e.to_string()),
+                    }
+                }
+            }
+            MessageSource::BulkRenameConfirmationPrompt(plan) => {
+                if let Some(Message::Bool(true)) = message {
+                    file_manager.bulk_rename(plan);
+                }
+            }
+            MessageSource::None => {}
+        }
+    }
+}
+impl MessageSender for ExplorerTable {
+    fn get_message(&mut self) -> Option<Message> {
+        self.message.take()
+    }
+}
 
-            _ => {}
+impl State for ExplorerTable {
+    fn enter(&mut self, file_manager: &mut FileManager) {
+        file_manager.update();
+    }
+
+    ///the preview is loading
+    fn is_busy(&self, _file_manager: &FileManager) -> bool {
+        self.previewer
+            .iter()
+            .chain(&self.miller_columns)
+            .any(Previewer::is_loading)
+    }
+
+    ///refresh the listing, the highlight stays on the same file if it still exists
+    fn files_changed(&mut self, file_manager: &mut FileManager) {
+        let highlighted = self.selected_file_in_table(file_manager);
+        file_manager.update();
+        match highlighted.and_then(|path| file_manager.index_of(&path)) {
+            Some(index) => self.table_state.select(Some(index)),
+            None if file_manager.num_files == 0 => self.table_state.select(None),
+            None => {
+                let index = self.table_state.selected().unwrap_or_default();
+                self.table_state
+                    .select(Some(index.min(file_manager.num_files - 1)));
+            }
+        }
+    }
+
+    fn exit(&mut self, _file_manager: &mut FileManager) {}
+
+    fn handle_key_event(
+        &mut self,
+        key_event: KeyEvent,
+        file_manager: &mut FileManager,
+    ) -> AppEvents {
+        if self.filter_editing {
+            self.handle_filter_key_event(key_event, file_manager);
+            return AppEvents::None;
+        }
+        if self.jobs_focused && !file_manager.jobs().is_empty() {
+            self.handle_jobs_key_event(key_event, file_manager);
+            return AppEvents::None;
+        }
+        self.jobs_focused = false;
+
+        if let Some(action) = self.pending_mark.take() {
+            if let KeyCode::Char(letter) = key_event.code {
+                match action {
+                    MarkAction::Set => file_manager.set_mark(letter),
+                    MarkAction::Jump => {
+                        file_manager.jump_to_mark(letter);
+                        self.table_state.select_first();
+                    }
+                }
+            }
+            return AppEvents::None;
+        }
+
+        let count = self.count.take();
+        //a count is typed before the keys of an action, digits bound in keys.toml aren't counts
+        if let KeyCode::Char(digit @ '0'..='9') = key_event.code
+            && self.pending_keys.is_empty()
+            && !key_event
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+            && (count.is_some() || digit != '0')
+            && matches!(
+                file_manager.keymap().lookup(&[Key::from_event(&key_event)]),
+                Lookup::None
+            )
+        {
+            let digit = digit.to_digit(10).unwrap_or_default() as usize;
+            self.count = Some(count.unwrap_or_default().saturating_mul(10) + digit);
+            return AppEvents::None;
+        }
+
+        self.pending_keys.push(Key::from_event(&key_event));
+        match file_manager.keymap().lookup(&self.pending_keys) {
+            //wait for the rest of the sequence, the count is kept until then
+            Lookup::Pending => self.count = count,
+            Lookup::None => self.pending_keys.clear(),
+            Lookup::Actions(actions) => {
+                self.pending_keys.clear();
+                for action in actions {
+                    if let Some(event) = self.run_action(action, count, file_manager) {
+                        return event;
+                    }
+                }
+            }
         }
         AppEvents::None
     }
@@ -233,14 +1089,58 @@ impl State for ExplorerTable {
             self.error_ring_buffer.push(x.to_string());
         }
 
-        let title = Line::from("FILE EXPLORER").bold();
-        let help_text = Line::from("Key Mappings:<m>");
+        let mut title = String::from("FILE EXPLORER");
+        match file_manager.pick_mode() {
+            Some(PickMode::Files) => title.push_str(" [PICK FILES]"),
+            Some(PickMode::Dirs) => title.push_str(" [PICK FOLDERS]"),
+            Some(PickMode::Any) => title.push_str(" [PICK]"),
+            None if file_manager.is_read_only() => title.push_str(" [READ-ONLY]"),
+            None => {}
+        }
+        if file_manager.is_cut() {
+            title.push_str(" [CUT]");
+        }
+        if let Some(filter) = file_manager.filter() {
+            title.push_str(&format!(
+                " [/{} {}/{}]",
+                filter.pattern(),
+                file_manager.num_files,
+                file_manager.total_entries()
+            ));
+        }
+        let title = Line::from(title).bold();
+        let keymap = file_manager.keymap();
+        let help_text = Line::from(format!("Key Mappings:{}", keymap.hint(Action::Help)));
         let table_block = Block::bordered()
             .title(title.left_aligned().bold())
             .border_set(border::THICK)
             .title_bottom(help_text.right_aligned().bold());
 
-        let path_block = Block::bordered().title("PATH").border_set(border::THICK);
+        //the path bar turns into the input of the filter while it is typed
+        let path_block = if self.filter_editing {
+            let mut path_block = Block::bordered()
+                .title(format!("FILTER ({})", self.filter_mode.label()))
+                .title_bottom(
+                    Line::from(format!(
+                        "mode:<Tab> apply:{} clear:{}",
+                        keymap.hint(Action::Confirm),
+                        keymap.hint(Action::Close)
+                    ))
+                    .right_aligned(),
+                )
+                .border_set(border::THICK);
+            if let Some(error) = &self.filter_error {
+                path_block = path_block.title(Line::from(error.clone()).red());
+            }
+            path_block
+        } else {
+            let path_block = Block::bordered().title("PATH").border_set(border::THICK);
+            if file_manager.tab_count() > 1 {
+                path_block.title(ExplorerTable::tab_bar(file_manager).right_aligned())
+            } else {
+                path_block
+            }
+        };
         let error_log_block = Block::bordered()
             .title("ERROR LOG")
             .border_set(border::THICK);
@@ -251,12 +1151,76 @@ impl State for ExplorerTable {
         let path_area = horizontal_layout[0];
         let main_area = horizontal_layout[1];
 
-        let vertical_layout =
-            Layout::horizontal([Constraint::Percentage(70), Constraint::Percentage(30)])
-                .split(main_area);
+        //the miller columns show the preview themselves
+        let preview_shown = self.previewer.is_some() && self.miller_columns.is_none();
+        let wide_table = self.panes.is_some() || self.miller_columns.is_some();
+        let table_width = match (preview_shown, wide_table) {
+            (true, _) => file_manager.config().preview_table_width,
+            (false, true) => 80,
+            (false, false) => file_manager.config().table_width,
+        };
+        let vertical_layout = Layout::horizontal([
+            Constraint::Percentage(table_width),
+            Constraint::Percentage(100 - table_width),
+        ])
+        .split(main_area);
 
         let table_area = vertical_layout[0];
-        let error_area = vertical_layout[1];
+        let mut error_area = vertical_layout[1];
+
+        //the preview takes the upper part of the right column
+        let highlighted = self.selected_file_in_table(file_manager);
+        if preview_shown && let Some(previewer) = &mut self.previewer {
+            let right_layout =
+                Layout::vertical([Constraint::Percentage(60), Constraint::Percentage(40)])
+                    .split(error_area);
+            error_area = right_layout[1];
+
+            let preview_block = Block::bordered().title("PREVIEW").border_set(border::THICK);
+            let preview = ExplorerTable::preview(previewer, highlighted.as_deref(), preview_block);
+            frame.render_widget(preview, right_layout[0]);
+        }
+
+        //the jobs panel shares the right column with the error log while there are jobs
+        let jobs = file_manager.jobs();
+        if !jobs.is_empty() {
+            let right_layout =
+                Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .split(error_area);
+            error_area = right_layout[1];
+
+            let mut jobs_block = Block::bordered().title("JOBS").border_set(border::THICK);
+            if self.jobs_focused {
+                jobs_block = jobs_block
+                    .title_bottom(
+                        Line::from(format!(
+                            "pause:{} cancel:{} back:{}",
+                            file_manager.keymap().hint(Action::PauseJob),
+                            file_manager.keymap().hint(Action::CancelJob),
+                            file_manager.keymap().hint(Action::Close)
+                        ))
+                        .right_aligned(),
+                    )
+                    .yellow();
+            } else {
+                let focus = format!("focus:{}", file_manager.keymap().hint(Action::FocusJobs));
+                jobs_block = jobs_block.title_bottom(Line::from(focus).right_aligned());
+            }
+            let si = file_manager.si_units;
+            let list = List::new(jobs.iter().map(|job| ExplorerTable::job_item(job, si)))
+                .block(jobs_block)
+                .highlight_style(if self.jobs_focused {
+                    Style::new().on_dark_gray()
+                } else {
+                    Style::new()
+                });
+            if self.jobs_state.selected().is_some_and(|i| i >= jobs.len()) {
+                self.jobs_state.select(Some(jobs.len() - 1));
+            }
+            frame.render_stateful_widget(list, right_layout[0], &mut self.jobs_state);
+        } else {
+            self.jobs_focused = false;
+        }
 
         let inner_path_area = path_block.inner(path_area);
         let inner_error_area = error_log_block.inner(error_area);
@@ -266,43 +1230,70 @@ impl State for ExplorerTable {
         });
 
         //write path to path_area
-        let path = file_manager
-            .current_dir()
-            .unwrap_or_default()
-            .into_os_string()
-            .into_string()
-            .unwrap_or_default();
+        let path = if self.filter_editing {
+            frame.set_cursor_position((
+                inner_path_area.x + self.filter_text.chars().count() as u16,
+                inner_path_area.y,
+            ));
+            self.filter_text.clone()
+        } else {
+            file_manager
+                .current_dir()
+                .into_os_string()
+                .into_string()
+                .unwrap_or_default()
+        };
 
         let text_paragraph = Paragraph::new(path).left_aligned().wrap(Wrap {
             ..Default::default()
         });
 
-        let mut rows: Vec<Row> = Vec::new();
-        let header = Row::new(vec!["FILENAME", "SIZE"]).bold().dark_gray();
-        for entry in file_manager.get_entries() {
-            let mut row_strings: Vec<String> = Vec::new();
-            row_strings.push(entry.file_name().into_string().unwrap());
-            if entry.metadata().unwrap().is_file() {
-                row_strings.push(entry.metadata().unwrap().len().to_string());
-            } else {
-                row_strings.push("".to_string());
+        match self.panes {
+            None if self.miller_columns.is_some() => {
+                self.draw_miller_columns(frame, file_manager, table_area, table_block, highlighted);
             }
-            let mut row = Row::new(row_strings);
-            if file_manager.is_selected(&entry.path()) {
-                row = row.on_dark_gray();
-            } else if entry.metadata().unwrap().is_dir() {
-                row = row.blue();
+            None => {
+                let table = self.table(
+                    file_manager.get_entries(),
+                    file_manager.tree_depths(),
+                    file_manager,
+                    table_area.width,
+                    table_block,
+                );
+                frame.render_stateful_widget(table, table_area, &mut self.table_state);
+            }
+            //the focused pane keeps the thick border, the other one is titled with its path
+            Some(panes) => {
+                let pane_areas =
+                    Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                        .split(table_area);
+                for (pane, area) in panes.into_iter().zip(pane_areas.iter().copied()) {
+                    if pane == file_manager.current_tab() {
+                        let table = self.table(
+                            file_manager.get_entries(),
+                            file_manager.tree_depths(),
+                            file_manager,
+                            area.width,
+                            table_block.clone(),
+                        );
+                        frame.render_stateful_widget(table, area, &mut self.table_state);
+                    } else {
+                        let dir = file_manager.tab_dirs().swap_remove(pane);
+                        let block = Block::bordered().title(dir.display().to_string());
+                        let table = self.table(
+                            file_manager.tab_entries(pane),
+                            &[],
+                            file_manager,
+                            area.width,
+                            block,
+                        );
+                        let mut table_state =
+                            TableState::new().with_selected(file_manager.tab_selected(pane));
+                        frame.render_stateful_widget(table, area, &mut table_state);
+                    }
+                }
             }
-            rows.push(row);
         }
-        let widths = [Constraint::Percentage(20), Constraint::Percentage(20)];
-
-        let table = Table::new(rows, widths)
-            .block(table_block)
-            .header(header)
-            .cell_highlight_style(Style::new().green());
-
-        frame.render_stateful_widget(table, table_area, &mut self.table_state);
         frame.render_widget(error_log_paragraph, inner_error_area);
         frame.render_widget(text_paragraph, inner_path_area);
         frame.render_widget(error_log_block, error_area);
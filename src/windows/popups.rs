@@ -1,5 +1,11 @@
+pub mod bookmarks_popup;
+pub mod columns_popup;
 pub mod confirmation_popup;
+pub mod conflict_popup;
+pub mod fuzzy_finder_popup;
+pub mod history_popup;
 pub mod key_mapping_popup;
 pub mod new_file_popup;
+pub mod recent_dirs_popup;
 pub mod sorting_popup;
 pub mod text_field_popup;
@@ -0,0 +1,135 @@
+use crate::controller::{AppEvents, State};
+use crate::file_manager::FileManager;
+use crate::keymap::Action;
+use crate::message::{Message, MessageReceiver, MessageSender};
+use crate::util;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::Frame;
+use ratatui::layout::Alignment::Center;
+use ratatui::prelude::{Line, Style, Stylize};
+use ratatui::widgets::{Block, Clear, List, ListState};
+use std::path::PathBuf;
+
+///Lists the marks and allows renaming and deleting them.
+///Answers with the directory of the chosen mark as Message::Paths.
+pub struct BookmarksPopup {
+    list_state: ListState,
+    //the next letter becomes the new name of the selected mark
+    renaming: bool,
+    chosen: Option<PathBuf>,
+}
+
+impl BookmarksPopup {
+    pub fn new() -> BookmarksPopup {
+        let mut popup = BookmarksPopup {
+            list_state: ListState::default(),
+            renaming: false,
+            chosen: None,
+        };
+        popup.list_state.select_first();
+        popup
+    }
+
+    fn selected_mark(&self, file_manager: &FileManager) -> Option<(char, PathBuf)> {
+        file_manager
+            .marks()
+            .into_iter()
+            .nth(self.list_state.selected()?)
+    }
+
+    fn jump(&mut self, file_manager: &FileManager) -> AppEvents {
+        self.chosen = self.selected_mark(file_manager).map(|(_, path)| path);
+        AppEvents::ClosePopUp
+    }
+}
+
+impl MessageReceiver for BookmarksPopup {}
+
+impl MessageSender for BookmarksPopup {
+    fn get_message(&mut self) -> Option<Message> {
+        self.chosen.take().map(|path| Message::Paths(vec![path]))
+    }
+}
+
+impl State for BookmarksPopup {
+    fn handle_key_event(
+        &mut self,
+        key_event: KeyEvent,
+        file_manager: &mut FileManager,
+    ) -> AppEvents {
+        if self.renaming {
+            self.renaming = false;
+            if let KeyCode::Char(letter) = key_event.code
+                && let Some((mark, _)) = self.selected_mark(file_manager)
+            {
+                file_manager.rename_mark(mark, letter);
+                let index = file_manager.marks().iter().position(|(l, _)| *l == letter);
+                self.list_state.select(index);
+            }
+            return AppEvents::None;
+        }
+
+        let keymap = file_manager.keymap();
+        let pressed = |action| keymap.is_bound(&key_event, action);
+        match keymap.popup_action(&key_event) {
+            Some(Action::Up) => self.list_state.select_previous(),
+            Some(Action::Down) => self.list_state.select_next(),
+            Some(Action::GoTop) => self.list_state.select_first(),
+            Some(Action::GoBottom) => self.list_state.select_last(),
+            Some(Action::Confirm) => return self.jump(file_manager),
+            Some(Action::Close) => return AppEvents::ClosePopUp,
+            _ if pressed(Action::EnterDir) => return self.jump(file_manager),
+            _ if pressed(Action::Bookmarks) => return AppEvents::ClosePopUp,
+            _ if pressed(Action::RenameMark) && self.selected_mark(file_manager).is_some() => {
+                self.renaming = true;
+            }
+            _ if pressed(Action::DeleteMark) => {
+                if let Some((mark, _)) = self.selected_mark(file_manager) {
+                    file_manager.remove_mark(mark);
+                }
+            }
+            _ => {}
+        }
+        AppEvents::None
+    }
+
+    fn draw(&mut self, frame: &mut Frame, file_manager: &mut FileManager) {
+        let area = frame.area();
+
+        let help_text = if self.renaming {
+            String::from("press the new letter")
+        } else {
+            let keymap = file_manager.keymap();
+            format!(
+                "jump:{} rename:{} delete:{}",
+                keymap.hint(Action::Confirm),
+                keymap.hint(Action::RenameMark),
+                keymap.hint(Action::DeleteMark)
+            )
+        };
+        let popup_block = Block::bordered()
+            .title("BOOKMARKS")
+            .title_alignment(Center)
+            .title_bottom(Line::from(help_text).right_aligned());
+        let popup_area = util::popup_area(area, 50, 50);
+
+        let mut items: Vec<Line> = file_manager
+            .marks()
+            .into_iter()
+            .map(|(letter, path)| Line::from(format!("{letter}  {}", path.display())))
+            .collect();
+        if items.is_empty() {
+            let set_mark = file_manager.keymap().hint(Action::SetMark);
+            items.push(
+                Line::from(format!("no marks yet, set one with {set_mark}<letter>")).dark_gray(),
+            );
+        }
+
+        let list = List::new(items)
+            .block(popup_block)
+            .highlight_style(Style::new().blue());
+
+        frame.render_widget(Clear, popup_area);
+        frame.render_stateful_widget(list, popup_area, &mut self.list_state);
+    }
+}
@@ -0,0 +1,161 @@
+use crate::columns::Column;
+use crate::controller::{AppEvents, State};
+use crate::file_manager::FileManager;
+use crate::keymap::Action;
+use crate::message::{MessageReceiver, MessageSender};
+use crate::util;
+use crossterm::event::KeyEvent;
+use ratatui::Frame;
+use ratatui::layout::Alignment::Center;
+use ratatui::prelude::{Line, Style, Stylize};
+use ratatui::widgets::{Block, Clear, List, ListState};
+
+///Choose which columns the explorer table shows and in which order.
+///Changes are applied to the file_manager immediately.
+pub struct ColumnsPopup {
+    list_state: ListState,
+}
+
+impl ColumnsPopup {
+    pub fn new() -> ColumnsPopup {
+        let mut popup = ColumnsPopup {
+            list_state: ListState::default(),
+        };
+        popup.list_state.select_first();
+        popup
+    }
+
+    /// the shown columns in their order followed by the hidden ones
+    fn entries(file_manager: &FileManager) -> Vec<(Column, bool)> {
+        let mut entries: Vec<(Column, bool)> = file_manager
+            .columns
+            .iter()
+            .map(|column| (*column, true))
+            .collect();
+        entries.extend(
+            Column::ALL
+                .iter()
+                .filter(|column| !file_manager.columns.contains(column))
+                .map(|column| (*column, false)),
+        );
+        entries
+    }
+
+    fn selected_column(&self, file_manager: &FileManager) -> Option<Column> {
+        let index = self.list_state.selected()?;
+        ColumnsPopup::entries(file_manager)
+            .get(index)
+            .map(|(column, _)| *column)
+    }
+
+    ///show or hide the selected column, the name is always shown
+    fn toggle_selected(&mut self, file_manager: &mut FileManager) {
+        match self.selected_column(file_manager) {
+            Some(Column::Name) | None => {}
+            Some(column) => {
+                if let Some(index) = file_manager.columns.iter().position(|c| *c == column) {
+                    file_manager.columns.remove(index);
+                } else {
+                    file_manager.columns.push(column);
+                }
+                let entries = ColumnsPopup::entries(file_manager);
+                let index = entries.iter().position(|(c, _)| *c == column);
+                self.list_state.select(index);
+            }
+        }
+    }
+
+    /// move a shown column one position up (negative) or down
+    fn move_column(&mut self, file_manager: &mut FileManager, offset: isize) {
+        let Some(index) = self.list_state.selected() else {
+            return;
+        };
+        let Some(target) = index.checked_add_signed(offset) else {
+            return;
+        };
+        if index < file_manager.columns.len() && target < file_manager.columns.len() {
+            file_manager.columns.swap(index, target);
+            self.list_state.select(Some(target));
+        }
+    }
+}
+
+impl MessageReceiver for ColumnsPopup {}
+impl MessageSender for ColumnsPopup {}
+
+impl State for ColumnsPopup {
+    fn handle_key_event(
+        &mut self,
+        key_event: KeyEvent,
+        file_manager: &mut FileManager,
+    ) -> AppEvents {
+        let keymap = file_manager.keymap();
+        match keymap.popup_action(&key_event) {
+            Some(Action::Up) => self.list_state.select_previous(),
+            Some(Action::Down) => self.list_state.select_next(),
+            Some(Action::GoTop) => self.list_state.select_first(),
+            Some(Action::GoBottom) => self.list_state.select_last(),
+            Some(Action::Confirm) => self.toggle_selected(file_manager),
+            Some(Action::Close) => return AppEvents::ClosePopUp,
+            _ if keymap.is_bound(&key_event, Action::Columns) => return AppEvents::ClosePopUp,
+            _ if keymap.is_bound(&key_event, Action::MoveColumnUp) => {
+                self.move_column(file_manager, -1)
+            }
+            _ if keymap.is_bound(&key_event, Action::MoveColumnDown) => {
+                self.move_column(file_manager, 1)
+            }
+            _ if keymap.is_bound(&key_event, Action::ToggleColumn) => {
+                self.toggle_selected(file_manager)
+            }
+            _ if keymap.is_bound(&key_event, Action::ToggleUnits) => {
+                file_manager.si_units = !file_manager.si_units
+            }
+            _ => {}
+        }
+        AppEvents::None
+    }
+
+    fn draw(&mut self, frame: &mut Frame, file_manager: &mut FileManager) {
+        let area = frame.area();
+
+        let units = if file_manager.si_units { "kB" } else { "KiB" };
+        let keymap = file_manager.keymap();
+        let popup_block = Block::bordered()
+            .title("COLUMNS")
+            .title_alignment(Center)
+            .title_bottom(
+                Line::from(format!(
+                    "toggle:{} move:{}/{}",
+                    keymap.hint(Action::ToggleColumn),
+                    keymap.hint(Action::MoveColumnDown),
+                    keymap.hint(Action::MoveColumnUp)
+                ))
+                .right_aligned(),
+            )
+            .title_bottom(
+                Line::from(format!(
+                    "units:{} {}",
+                    keymap.hint(Action::ToggleUnits),
+                    units
+                ))
+                .left_aligned(),
+            );
+        let mut popup_area = util::popup_area(area, 40, 50);
+
+        let items: Vec<Line> = ColumnsPopup::entries(file_manager)
+            .into_iter()
+            .map(|(column, shown)| {
+                let checkbox = if shown { "[x]" } else { "[ ]" };
+                let line = Line::from(format!("{checkbox} {}", column.description()));
+                if shown { line } else { line.dark_gray() }
+            })
+            .collect();
+        let list = List::new(items)
+            .block(popup_block)
+            .highlight_style(Style::new().blue());
+
+        popup_area.height = list.len() as u16 + 2;
+        frame.render_widget(Clear, popup_area);
+        frame.render_stateful_widget(list, popup_area, &mut self.list_state);
+    }
+}
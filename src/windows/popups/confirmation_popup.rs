@@ -1,4 +1,4 @@
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::KeyEvent;
 use ratatui::{
     layout::{Constraint, Layout},
     style::{Style, Stylize},
@@ -9,6 +9,7 @@ use ratatui::{
 use crate::{
     controller::{AppEvents, State},
     file_manager::FileManager,
+    keymap::Action,
     message::{Message, MessageReceiver, MessageSender},
     util,
 };
@@ -36,19 +37,19 @@ impl State for ConfirmationPopup {
     fn handle_key_event(
         &mut self,
         key_event: KeyEvent,
-        _file_manager: &mut FileManager,
+        file_manager: &mut FileManager,
     ) -> AppEvents {
-        match key_event.code {
-            KeyCode::Enter => {
+        match file_manager.keymap().popup_action(&key_event) {
+            Some(Action::Confirm) => {
                 match self.list_state.selected() {
                     Some(0) => self.confirmation_result = Some(false),
                     _ => self.confirmation_result = Some(true),
                 }
                 return AppEvents::ClosePopUp;
             }
-            KeyCode::Esc => return AppEvents::ClosePopUp,
-            KeyCode::Up | KeyCode::Char('k') => self.list_state.select(Some(0)),
-            KeyCode::Down | KeyCode::Char('j') => self.list_state.select(Some(1)),
+            Some(Action::Close) => return AppEvents::ClosePopUp,
+            Some(Action::Up | Action::GoTop) => self.list_state.select(Some(0)),
+            Some(Action::Down | Action::GoBottom) => self.list_state.select(Some(1)),
             _ => {}
         }
         AppEvents::None
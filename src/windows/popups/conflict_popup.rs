@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crossterm::event::KeyEvent;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, Clear, List, ListState, Paragraph, Wrap},
+};
+
+use crate::{
+    controller::{AppEvents, State},
+    file_manager::FileManager,
+    file_operations::{ConflictPolicy, MergeFiles},
+    keymap::Action,
+    message::{Message, MessageReceiver, MessageSender},
+    util,
+};
+
+///Asks how to handle every pasted entry whose name already exists in the target directory.
+///Receives the target and the conflicting sources as Message::PasteConflicts and answers with
+///Message::ConflictResolutions once all conflicts are resolved. Esc cancels the whole paste.
+pub struct ConflictPopup {
+    target: PathBuf,
+    conflicts: Vec<PathBuf>,
+    resolutions: HashMap<PathBuf, ConflictPolicy>,
+    apply_to_all: bool,
+    finished: bool,
+    list_state: ListState,
+}
+
+impl ConflictPopup {
+    pub fn new(message: Option<Message>, file_manager: &mut FileManager) -> ConflictPopup {
+        let mut popup = ConflictPopup {
+            target: PathBuf::new(),
+            conflicts: Vec::new(),
+            resolutions: HashMap::new(),
+            apply_to_all: false,
+            finished: false,
+            list_state: ListState::default(),
+        };
+        popup.list_state.select(Some(0));
+        popup.handle_message(message, file_manager);
+        popup
+    }
+
+    fn current_conflict(&self) -> Option<&PathBuf> {
+        self.conflicts.get(self.resolutions.len())
+    }
+
+    ///options for the current conflict, merging is only possible if both sides are directories
+    fn options(&self) -> Vec<ConflictPolicy> {
+        let mut options = vec![
+            ConflictPolicy::Overwrite,
+            ConflictPolicy::Skip,
+            ConflictPolicy::Rename,
+        ];
+        if let Some(src) = self.current_conflict()
+            && src.is_dir()
+            && self
+                .target
+                .join(src.file_name().unwrap_or_default())
+                .is_dir()
+        {
+            options.extend([
+                ConflictPolicy::Merge(MergeFiles::Overwrite),
+                ConflictPolicy::Merge(MergeFiles::Skip),
+                ConflictPolicy::Merge(MergeFiles::Rename),
+            ]);
+        }
+        options
+    }
+
+    fn resolve(&mut self, policy: ConflictPolicy) {
+        if self.apply_to_all {
+            for src in &self.conflicts {
+                self.resolutions.entry(src.clone()).or_insert(policy);
+            }
+        } else if let Some(src) = self.current_conflict().cloned() {
+            self.resolutions.insert(src, policy);
+        }
+        self.finished = self.current_conflict().is_none();
+        self.list_state.select(Some(0));
+    }
+}
+
+impl MessageReceiver for ConflictPopup {
+    fn handle_message(&mut self, message: Option<Message>, _file_manager: &mut FileManager) {
+        if let Some(Message::PasteConflicts(target, conflicts)) = message {
+            self.target = target;
+            self.conflicts = conflicts;
+        }
+    }
+}
+
+impl MessageSender for ConflictPopup {
+    fn get_message(&mut self) -> Option<Message> {
+        if self.finished {
+            Some(Message::ConflictResolutions(std::mem::take(
+                &mut self.resolutions,
+            )))
+        } else {
+            None
+        }
+    }
+}
+
+impl State for ConflictPopup {
+    fn handle_key_event(
+        &mut self,
+        key_event: KeyEvent,
+        file_manager: &mut FileManager,
+    ) -> AppEvents {
+        match file_manager.keymap().popup_action(&key_event) {
+            Some(Action::Up) => self.list_state.select_previous(),
+            Some(Action::Down) => self.list_state.select_next(),
+            Some(Action::GoTop) => self.list_state.select_first(),
+            Some(Action::GoBottom) => self.list_state.select_last(),
+            Some(Action::Close) => return AppEvents::ClosePopUp,
+            Some(Action::Confirm) => {
+                let options = self.options();
+                let index = self.list_state.selected().unwrap_or_default();
+                if let Some(policy) = options.get(index.min(options.len() - 1)) {
+                    self.resolve(*policy);
+                }
+                if self.finished {
+                    return AppEvents::ClosePopUp;
+                }
+            }
+            _ if file_manager
+                .keymap()
+                .is_bound(&key_event, Action::ApplyToAll) =>
+            {
+                self.apply_to_all = !self.apply_to_all
+            }
+            _ => {}
+        }
+        AppEvents::None
+    }
+
+    fn draw(&mut self, frame: &mut Frame, file_manager: &mut FileManager) {
+        let Some(src) = self.current_conflict() else {
+            return;
+        };
+        let area = frame.area();
+        let popup_area = util::popup_area(area, 40, 40);
+
+        let remaining = self.conflicts.len() - self.resolutions.len();
+        let text_block = Block::bordered().title(format!("Conflict ({remaining} left)"));
+        let options_block = Block::bordered();
+
+        let options = self.options();
+        let layout = Layout::vertical([
+            Constraint::Min(3),
+            Constraint::Length(options.len() as u16 + 2),
+            Constraint::Length(1),
+        ])
+        .split(popup_area);
+
+        let text = format!(
+            "\"{}\" already exists in {}",
+            src.file_name().unwrap_or_default().to_string_lossy(),
+            self.target.display()
+        );
+        let paragraph = Paragraph::new(text)
+            .centered()
+            .wrap(Wrap { trim: true })
+            .block(text_block);
+
+        let list = List::new(
+            options
+                .iter()
+                .map(|policy| Line::from(policy.label()).centered()),
+        )
+        .block(options_block)
+        .highlight_style(Style::new().blue());
+
+        let checkbox = if self.apply_to_all { "[x]" } else { "[ ]" };
+        let apply_to_all = Line::from(format!(
+            "{checkbox} apply to all {}",
+            file_manager.keymap().hint(Action::ApplyToAll)
+        ))
+        .centered();
+
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(paragraph, layout[0]);
+        frame.render_stateful_widget(list, layout[1], &mut self.list_state);
+        frame.render_widget(apply_to_all, layout[2]);
+    }
+}
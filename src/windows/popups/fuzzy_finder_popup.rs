@@ -0,0 +1,227 @@
+use std::path::PathBuf;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout},
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Clear, List, ListState, Paragraph},
+};
+
+use crate::{
+    controller::{AppEvents, State},
+    file_manager::FileManager,
+    fuzzy::{self, FuzzyMatch, TreeWalker},
+    keymap::{self, Action},
+    message::{Message, MessageReceiver, MessageSender},
+    util,
+};
+
+///Finds files below the current directory by typing parts of their path. The tree is walked in
+///the background, matches are ranked while typing. Answers with the chosen path as
+///Message::Paths.
+pub struct FuzzyFinderPopup {
+    root: PathBuf,
+    walker: TreeWalker,
+    //relative paths of all entries found so far
+    entries: Vec<String>,
+    query: String,
+    //index into entries and the match, best match first
+    matches: Vec<(usize, FuzzyMatch)>,
+    list_state: ListState,
+    chosen: Option<PathBuf>,
+}
+
+impl FuzzyFinderPopup {
+    pub fn new(file_manager: &mut FileManager) -> FuzzyFinderPopup {
+        let root = file_manager.current_dir();
+        let walker = TreeWalker::new(
+            &root,
+            file_manager.show_hidden,
+            file_manager.respect_gitignore,
+        );
+        let mut popup = FuzzyFinderPopup {
+            root,
+            walker,
+            entries: Vec::new(),
+            query: String::new(),
+            matches: Vec::new(),
+            list_state: ListState::default(),
+            chosen: None,
+        };
+        popup.list_state.select_first();
+        popup
+    }
+
+    /// restart the walk, e.g. after the ignore settings changed
+    fn restart_walk(&mut self, file_manager: &FileManager) {
+        self.walker = TreeWalker::new(
+            &self.root,
+            file_manager.show_hidden,
+            file_manager.respect_gitignore,
+        );
+        self.entries.clear();
+        self.matches.clear();
+    }
+
+    /// score the entries starting at `first` and merge them into the sorted matches
+    fn match_entries(&mut self, first: usize) {
+        for (index, entry) in self.entries.iter().enumerate().skip(first) {
+            if let Some(found) = fuzzy::fuzzy_match(&self.query, entry) {
+                self.matches.push((index, found));
+            }
+        }
+        self.matches
+            .sort_by(|(a_index, a), (b_index, b)| b.score.cmp(&a.score).then(a_index.cmp(b_index)));
+    }
+
+    fn rematch(&mut self) {
+        self.matches.clear();
+        self.match_entries(0);
+        self.list_state.select_first();
+    }
+
+    /// the entry with its matched characters highlighted
+    fn highlighted_line(entry: &str, found: &FuzzyMatch) -> Line<'static> {
+        let mut positions = found.positions.iter().peekable();
+        let spans: Vec<Span> = entry
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                if positions.next_if_eq(&&i).is_some() {
+                    Span::from(c.to_string()).yellow().bold()
+                } else {
+                    Span::from(c.to_string())
+                }
+            })
+            .collect();
+        Line::from(spans)
+    }
+}
+
+impl MessageReceiver for FuzzyFinderPopup {}
+
+impl MessageSender for FuzzyFinderPopup {
+    fn get_message(&mut self) -> Option<Message> {
+        self.chosen.take().map(|path| Message::Paths(vec![path]))
+    }
+}
+
+impl State for FuzzyFinderPopup {
+    ///the walk is still finding entries
+    fn is_busy(&self, _file_manager: &FileManager) -> bool {
+        !self.walker.is_finished()
+    }
+
+    fn handle_key_event(
+        &mut self,
+        key_event: KeyEvent,
+        file_manager: &mut FileManager,
+    ) -> AppEvents {
+        let control = key_event.modifiers.contains(KeyModifiers::CONTROL);
+        //typed characters go into the query
+        let action = if keymap::is_text(&key_event) {
+            None
+        } else {
+            file_manager.keymap().popup_action(&key_event)
+        };
+        match key_event.code {
+            _ if action == Some(Action::Close) => return AppEvents::ClosePopUp,
+            _ if action == Some(Action::Confirm) => {
+                let selected = self.list_state.selected().unwrap_or_default();
+                if let Some((index, _)) = self.matches.get(selected) {
+                    self.chosen = Some(self.root.join(&self.entries[*index]));
+                }
+                return AppEvents::ClosePopUp;
+            }
+            _ if action == Some(Action::Up) => self.list_state.select_previous(),
+            _ if action == Some(Action::Down) => self.list_state.select_next(),
+            _ if action == Some(Action::GoTop) => self.list_state.select_first(),
+            _ if action == Some(Action::GoBottom) => self.list_state.select_last(),
+            //toggle .gitignore handling, the walk starts over
+            _ if !keymap::is_text(&key_event)
+                && file_manager
+                    .keymap()
+                    .is_bound(&key_event, Action::ToggleGitignore) =>
+            {
+                file_manager.respect_gitignore = !file_manager.respect_gitignore;
+                self.restart_walk(file_manager);
+            }
+            KeyCode::Char(c) => {
+                self.query.push(c);
+                self.rematch();
+            }
+            KeyCode::Backspace => {
+                if control {
+                    self.query.clear();
+                } else {
+                    self.query.pop();
+                }
+                self.rematch();
+            }
+            _ => {}
+        }
+        AppEvents::None
+    }
+
+    fn draw(&mut self, frame: &mut Frame, file_manager: &mut FileManager) {
+        let new_entries = self.walker.take_new();
+        if !new_entries.is_empty() {
+            let first = self.entries.len();
+            self.entries.extend(new_entries);
+            self.match_entries(first);
+        }
+
+        let area = frame.area();
+        let popup_area = util::popup_area(area, 60, 60);
+        let layout =
+            Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).split(popup_area);
+
+        let walking = if self.walker.is_finished() {
+            ""
+        } else {
+            " searching…"
+        };
+        let gitignore = if file_manager.respect_gitignore {
+            "on"
+        } else {
+            "off"
+        };
+        let input_block = Block::bordered().title(format!(
+            "Find ({}/{}){}",
+            self.matches.len(),
+            self.entries.len(),
+            walking
+        ));
+        let list_block = Block::bordered().title_bottom(
+            Line::from(format!(
+                ".gitignore {gitignore}:{}",
+                file_manager.keymap().hint(Action::ToggleGitignore)
+            ))
+            .right_aligned(),
+        );
+
+        //only the visible part of the matches gets rendered
+        let visible = layout[1].height.saturating_sub(2) as usize;
+        let selected = self.list_state.selected().unwrap_or_default();
+        let end = (selected + 1).max(visible).min(self.matches.len());
+        let items: Vec<Line> = self.matches[..end]
+            .iter()
+            .map(|(index, found)| FuzzyFinderPopup::highlighted_line(&self.entries[*index], found))
+            .collect();
+        let list = List::new(items)
+            .block(list_block)
+            .highlight_style(Style::new().on_dark_gray());
+
+        let input = Paragraph::new(self.query.as_str()).block(input_block);
+
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(input, layout[0]);
+        frame.render_stateful_widget(list, layout[1], &mut self.list_state);
+        frame.set_cursor_position((
+            layout[0].x + 1 + self.query.chars().count() as u16,
+            layout[0].y + 1,
+        ));
+    }
+}
@@ -0,0 +1,87 @@
+use crate::controller::{AppEvents, State};
+use crate::file_manager::FileManager;
+use crate::keymap::Action;
+use crate::message::{MessageReceiver, MessageSender};
+use crate::util;
+use crossterm::event::KeyEvent;
+use ratatui::Frame;
+use ratatui::layout::Alignment::Center;
+use ratatui::prelude::{Line, Style, Stylize};
+use ratatui::widgets::{Block, Clear, List, ListState};
+
+///Lists the entries of the operation journal, undone operations are shown grayed out
+pub struct HistoryPopup {
+    list_state: ListState,
+}
+
+impl HistoryPopup {
+    pub fn new() -> HistoryPopup {
+        let mut popup = HistoryPopup {
+            list_state: ListState::default(),
+        };
+        popup.list_state.select_first();
+        popup
+    }
+}
+
+impl MessageReceiver for HistoryPopup {}
+impl MessageSender for HistoryPopup {}
+
+impl State for HistoryPopup {
+    fn handle_key_event(
+        &mut self,
+        key_event: KeyEvent,
+        file_manager: &mut FileManager,
+    ) -> AppEvents {
+        let keymap = file_manager.keymap();
+        match keymap.popup_action(&key_event) {
+            Some(Action::Up) => self.list_state.select_previous(),
+            Some(Action::Down) => self.list_state.select_next(),
+            Some(Action::GoTop) => self.list_state.select_first(),
+            Some(Action::GoBottom) => self.list_state.select_last(),
+            Some(Action::Confirm | Action::Close) => return AppEvents::ClosePopUp,
+            _ if keymap.is_bound(&key_event, Action::History) => return AppEvents::ClosePopUp,
+            _ => {}
+        }
+        AppEvents::None
+    }
+
+    fn draw(&mut self, frame: &mut Frame, file_manager: &mut FileManager) {
+        let area = frame.area();
+
+        let keymap = file_manager.keymap();
+        let help_text = format!(
+            "undo:{} redo:{}",
+            keymap.hint(Action::Undo),
+            keymap.hint(Action::Redo)
+        );
+        let popup_block = Block::bordered()
+            .title("HISTORY")
+            .title_alignment(Center)
+            .title_bottom(Line::from(help_text).right_aligned());
+        let popup_area = util::popup_area(area, 50, 50);
+
+        let journal = file_manager.journal();
+        //the most recent operation is on top
+        let mut items: Vec<Line> = journal
+            .redo_entries()
+            .rev()
+            .map(|entry| Line::from(format!("(undone) {}", entry.description)).dark_gray())
+            .collect();
+        items.extend(
+            journal
+                .undo_entries()
+                .map(|entry| Line::from(entry.description.clone())),
+        );
+        if items.is_empty() {
+            items.push(Line::from("no operations yet").dark_gray());
+        }
+
+        let list = List::new(items)
+            .block(popup_block)
+            .highlight_style(Style::new().red());
+
+        frame.render_widget(Clear, popup_area);
+        frame.render_stateful_widget(list, popup_area, &mut self.list_state);
+    }
+}
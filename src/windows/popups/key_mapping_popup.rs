@@ -1,6 +1,7 @@
 use crate::controller::{AppEvents, State};
 use crate::file_manager::FileManager;
-use crate::message::{MessageReceiver, MessageSender};
+use crate::keymap::Action;
+use crate::message::{Message, MessageReceiver, MessageSender};
 use crate::util;
 use crossterm::event::KeyEvent;
 use ratatui::Frame;
@@ -8,27 +9,50 @@ use ratatui::layout::Alignment::Center;
 use ratatui::prelude::{Style, Stylize};
 use ratatui::widgets::{Block, Clear, List};
 
-pub struct KeyMappingPopup;
+pub struct KeyMappingPopup {
+    //one line per action, generated from the active keymap
+    lines: Vec<String>,
+    //the help is longer than most terminals, it scrolls with the up and down keys
+    scroll: usize,
+}
 
 impl KeyMappingPopup {
-    pub fn new() -> KeyMappingPopup {
-        KeyMappingPopup
+    pub fn new(message: Option<Message>, file_manager: &mut FileManager) -> KeyMappingPopup {
+        let mut popup = KeyMappingPopup {
+            lines: Vec::new(),
+            scroll: 0,
+        };
+        popup.handle_message(message, file_manager);
+        popup
     }
 }
 
-impl MessageReceiver for KeyMappingPopup {}
+impl MessageReceiver for KeyMappingPopup {
+    fn handle_message(&mut self, message: Option<Message>, _file_manager: &mut FileManager) {
+        if let Some(Message::StringList(lines)) = message {
+            self.lines = lines;
+        }
+    }
+}
 impl MessageSender for KeyMappingPopup {}
 
 impl State for KeyMappingPopup {
     fn handle_key_event(
         &mut self,
-        _key_event: KeyEvent,
-        _file_manager: &mut FileManager,
+        key_event: KeyEvent,
+        file_manager: &mut FileManager,
     ) -> AppEvents {
-        AppEvents::ClosePopUp
+        match file_manager.keymap().popup_action(&key_event) {
+            Some(Action::Down) => self.scroll += 1,
+            Some(Action::Up) => self.scroll = self.scroll.saturating_sub(1),
+            Some(Action::GoTop) => self.scroll = 0,
+            Some(Action::GoBottom) => self.scroll = self.lines.len(),
+            _ => return AppEvents::ClosePopUp,
+        }
+        AppEvents::None
     }
 
-    fn draw(&mut self, frame: &mut Frame, _file_manager: &mut FileManager) {
+    fn draw(&mut self, frame: &mut Frame, file_manager: &mut FileManager) {
         let area = frame.area();
 
         //let vertical = Layout::vertical([Constraint::Percentage(20), Constraint::Percentage(80)]);
@@ -37,27 +61,36 @@ impl State for KeyMappingPopup {
         let popup_block = Block::bordered()
             .title("KEY MAPPINGS")
             .title_alignment(Center);
-        let mut popup_area = util::popup_area(area, 20, 30);
+        let mut popup_area = util::popup_area(area, 40, 30);
+
+        let mut lines = self.lines.clone();
+        let keymap = file_manager.keymap();
+        lines.push(format!(
+            "<0-9> → count before {}/{}/{}/{}",
+            keymap.hint(Action::Undo),
+            keymap.hint(Action::Redo),
+            keymap.hint(Action::NextMatch),
+            keymap.hint(Action::PreviousMatch)
+        ));
+        let marks = file_manager.marks();
+        if !marks.is_empty() {
+            lines.push(String::new());
+            lines.push("marks:".to_owned());
+        }
+        for (letter, path) in marks {
+            lines.push(format!("'{letter} → {}", path.display()));
+        }
 
-        let list = List::new(vec![
-            "<Enter> → open file/folder".to_owned(),
-            "<Tab> → change path".to_owned(),
-            "<c> → clear selection".to_owned(),
-            "<v> → paste selection".to_owned(),
-            "<x> → delete selection".to_owned(),
-            "<y> → select/unselect".to_owned(),
-            "<g> → toggle hidden files".to_owned(),
-            "<d> → change folder positions".to_owned(),
-            "<s> → open sorting popup".to_owned(),
-            "<n> → create new file/folder".to_owned(),
-            "<q> → quit file explorer".to_owned(),
-            "<j/k/h/l> → navigate".to_owned(),
-            "<↑/↓/←/→> → navigate".to_owned(),
-        ])
-        .block(popup_block)
-        .highlight_style(Style::new().red());
+        //the list is long, center it by its own height so it fits into small terminals
+        popup_area.height = (lines.len() as u16 + 2).min(area.height);
+        popup_area.y = area.y + (area.height - popup_area.height) / 2;
+        let visible = popup_area.height.saturating_sub(2) as usize;
+        self.scroll = self.scroll.min(lines.len().saturating_sub(visible));
+        lines.drain(..self.scroll);
 
-        popup_area.height = list.len() as u16 + 2;
+        let list = List::new(lines)
+            .block(popup_block)
+            .highlight_style(Style::new().red());
         frame.render_widget(Clear, popup_area);
         frame.render_widget(list, popup_area);
     }
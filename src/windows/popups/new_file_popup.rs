@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::KeyEvent;
 use ratatui::{
     Frame,
     style::{Style, Stylize},
@@ -10,6 +10,7 @@ use ratatui::{
 use crate::{
     controller::{AppEvents, State},
     file_manager::FileManager,
+    keymap::Action,
     message::{Message, MessageReceiver, MessageSender},
     util,
 };
@@ -66,13 +67,15 @@ impl State for NewFilePopup {
     fn handle_key_event(
         &mut self,
         key_event: KeyEvent,
-        _file_manager: &mut FileManager,
+        file_manager: &mut FileManager,
     ) -> AppEvents {
-        match key_event.code {
-            KeyCode::Up | KeyCode::Char('k') => self.list_state.select_previous(),
-            KeyCode::Down | KeyCode::Char('j') => self.list_state.select_next(),
-            KeyCode::Esc => return AppEvents::ClosePopUp,
-            KeyCode::Enter => return AppEvents::OpenTextFieldPopup,
+        match file_manager.keymap().popup_action(&key_event) {
+            Some(Action::Up) => self.list_state.select_previous(),
+            Some(Action::Down) => self.list_state.select_next(),
+            Some(Action::GoTop) => self.list_state.select_first(),
+            Some(Action::GoBottom) => self.list_state.select_last(),
+            Some(Action::Close) => return AppEvents::ClosePopUp,
+            Some(Action::Confirm) => return AppEvents::OpenTextFieldPopup,
             _ => {}
         }
         AppEvents::None
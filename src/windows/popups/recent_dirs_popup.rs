@@ -0,0 +1,189 @@
+use std::path::PathBuf;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout},
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Clear, List, ListState, Paragraph},
+};
+
+use crate::{
+    controller::{AppEvents, State},
+    file_manager::FileManager,
+    fuzzy::{self, FuzzyMatch},
+    keymap::{self, Action},
+    message::{Message, MessageReceiver, MessageSender},
+    util,
+};
+
+///Lists the recently visited directories, the most frecent first. Typing narrows the list.
+///Answers with the chosen directory as Message::Paths.
+pub struct RecentDirsPopup {
+    dirs: Vec<String>,
+    query: String,
+    //index into dirs and the match, in the order they are shown
+    matches: Vec<(usize, FuzzyMatch)>,
+    list_state: ListState,
+    chosen: Option<PathBuf>,
+}
+
+impl RecentDirsPopup {
+    pub fn new(file_manager: &mut FileManager) -> RecentDirsPopup {
+        let mut popup = RecentDirsPopup {
+            dirs: Vec::new(),
+            query: String::new(),
+            matches: Vec::new(),
+            list_state: ListState::default(),
+            chosen: None,
+        };
+        popup.reload(file_manager);
+        popup
+    }
+
+    fn reload(&mut self, file_manager: &FileManager) {
+        self.dirs = file_manager
+            .recent_dirs()
+            .iter()
+            .map(|dir| dir.to_string_lossy().into_owned())
+            .collect();
+        self.rematch();
+    }
+
+    /// with a query the best matches come first, ties keep the frecency order
+    fn rematch(&mut self) {
+        self.matches = self
+            .dirs
+            .iter()
+            .enumerate()
+            .filter_map(|(index, dir)| Some((index, fuzzy::fuzzy_match(&self.query, dir)?)))
+            .collect();
+        self.matches
+            .sort_by(|(a_index, a), (b_index, b)| b.score.cmp(&a.score).then(a_index.cmp(b_index)));
+        self.list_state.select_first();
+    }
+
+    fn selected_dir(&self) -> Option<PathBuf> {
+        let (index, _) = self.matches.get(self.list_state.selected()?)?;
+        Some(PathBuf::from(&self.dirs[*index]))
+    }
+
+    /// the directory with its matched characters highlighted
+    fn highlighted_line(dir: &str, found: &FuzzyMatch) -> Line<'static> {
+        let mut positions = found.positions.iter().peekable();
+        let spans: Vec<Span> = dir
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                if positions.next_if_eq(&&i).is_some() {
+                    Span::from(c.to_string()).yellow().bold()
+                } else {
+                    Span::from(c.to_string())
+                }
+            })
+            .collect();
+        Line::from(spans)
+    }
+}
+
+impl MessageReceiver for RecentDirsPopup {}
+
+impl MessageSender for RecentDirsPopup {
+    fn get_message(&mut self) -> Option<Message> {
+        self.chosen.take().map(|path| Message::Paths(vec![path]))
+    }
+}
+
+impl State for RecentDirsPopup {
+    fn handle_key_event(
+        &mut self,
+        key_event: KeyEvent,
+        file_manager: &mut FileManager,
+    ) -> AppEvents {
+        let control = key_event.modifiers.contains(KeyModifiers::CONTROL);
+        //typed characters go into the query
+        let action = if keymap::is_text(&key_event) {
+            None
+        } else {
+            file_manager.keymap().popup_action(&key_event)
+        };
+        match key_event.code {
+            _ if action == Some(Action::Close) => return AppEvents::ClosePopUp,
+            _ if action == Some(Action::Confirm) => {
+                self.chosen = self.selected_dir();
+                return AppEvents::ClosePopUp;
+            }
+            _ if action == Some(Action::Up) => self.list_state.select_previous(),
+            _ if action == Some(Action::Down) => self.list_state.select_next(),
+            _ if action == Some(Action::GoTop) => self.list_state.select_first(),
+            _ if action == Some(Action::GoBottom) => self.list_state.select_last(),
+            //forget the selected directory
+            _ if !keymap::is_text(&key_event)
+                && file_manager
+                    .keymap()
+                    .is_bound(&key_event, Action::ForgetDir) =>
+            {
+                if let Some(dir) = self.selected_dir() {
+                    let selected = self.list_state.selected();
+                    file_manager.forget_recent_dir(&dir);
+                    self.reload(file_manager);
+                    self.list_state.select(selected);
+                }
+            }
+            KeyCode::Char(c) => {
+                self.query.push(c);
+                self.rematch();
+            }
+            KeyCode::Backspace => {
+                if control {
+                    self.query.clear();
+                } else {
+                    self.query.pop();
+                }
+                self.rematch();
+            }
+            _ => {}
+        }
+        AppEvents::None
+    }
+
+    fn draw(&mut self, frame: &mut Frame, file_manager: &mut FileManager) {
+        let area = frame.area();
+        let popup_area = util::popup_area(area, 60, 60);
+        let layout =
+            Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).split(popup_area);
+
+        let input_block = Block::bordered().title(format!(
+            "Recent directories ({}/{})",
+            self.matches.len(),
+            self.dirs.len()
+        ));
+        let list_block = Block::bordered().title_bottom(
+            Line::from(format!(
+                "forget:{}",
+                file_manager.keymap().hint(Action::ForgetDir)
+            ))
+            .right_aligned(),
+        );
+
+        let items: Vec<Line> = self
+            .matches
+            .iter()
+            .map(|(index, found)| RecentDirsPopup::highlighted_line(&self.dirs[*index], found))
+            .collect();
+        let list = List::new(items)
+            .block(list_block)
+            .highlight_style(Style::new().on_dark_gray());
+
+        let input = Paragraph::new(self.query.as_str()).block(input_block);
+
+        frame.render_widget(Clear, popup_area);
+        frame.render_widget(input, layout[0]);
+        frame.render_stateful_widget(list, layout[1], &mut self.list_state);
+        frame.set_cursor_position((
+            layout[0].x + 1 + self.query.chars().count() as u16,
+            layout[0].y + 1,
+        ));
+    }
+}
@@ -1,8 +1,9 @@
 use crate::controller::{AppEvents, State};
 use crate::file_manager::{FileManager, Sorting};
+use crate::keymap::Action;
 use crate::message::{MessageReceiver, MessageSender};
 use crate::util;
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::KeyEvent;
 use ratatui::Frame;
 use ratatui::style::{Style, Stylize};
 use ratatui::widgets::{Block, Clear, List, ListState};
@@ -46,17 +47,20 @@ impl State for SortingPopUp {
         key_event: KeyEvent,
         file_manager: &mut FileManager,
     ) -> AppEvents {
-        match key_event.code {
-            KeyCode::Up | KeyCode::Char('k') => self.list_state.select_previous(),
-            KeyCode::Down | KeyCode::Char('j') => self.list_state.select_next(),
-            KeyCode::Enter => match self.selected_sort_mode() {
+        match file_manager.keymap().popup_action(&key_event) {
+            Some(Action::Up) => self.list_state.select_previous(),
+            Some(Action::Down) => self.list_state.select_next(),
+            Some(Action::GoTop) => self.list_state.select_first(),
+            Some(Action::GoBottom) => self.list_state.select_last(),
+            Some(Action::Confirm) => match self.selected_sort_mode() {
                 None => return AppEvents::None,
                 Some(sorting) => {
                     file_manager.sort(sorting);
+                    file_manager.save_preferences();
                     return AppEvents::ClosePopUp;
                 }
             },
-            KeyCode::Esc => return AppEvents::ClosePopUp,
+            Some(Action::Close) => return AppEvents::ClosePopUp,
             _ => {}
         };
         AppEvents::None
@@ -4,6 +4,7 @@ use ratatui::widgets::{Block, Clear, Paragraph};
 use crate::{
     controller::{AppEvents, State},
     file_manager::FileManager,
+    keymap::{self, Action},
     message::{Message, MessageReceiver, MessageSender},
 };
 
@@ -56,9 +57,24 @@ impl State for TextFieldPopup {
     fn handle_key_event(
         &mut self,
         key_event: KeyEvent,
-        _file_manager: &mut FileManager,
+        file_manager: &mut FileManager,
     ) -> AppEvents {
+        //typed characters are text, e.g. <q> doesn't close
+        let action = if keymap::is_text(&key_event) {
+            None
+        } else {
+            file_manager.keymap().popup_action(&key_event)
+        };
         match key_event.code {
+            _ if action == Some(Action::Close) => {
+                self.string.clear();
+                return AppEvents::ClosePopUp;
+            }
+            _ if action == Some(Action::Confirm) => {
+                self.message = String::from(&self.string);
+                self.string.clear();
+                return AppEvents::ClosePopUp;
+            }
             KeyCode::Char(c) => self.string.push(c),
             KeyCode::Backspace => {
                 if key_event.modifiers.contains(KeyModifiers::CONTROL) {
@@ -67,15 +83,6 @@ impl State for TextFieldPopup {
                     self.string.pop();
                 }
             }
-            KeyCode::Esc => {
-                self.string.clear();
-                return AppEvents::ClosePopUp;
-            }
-            KeyCode::Enter => {
-                self.message = String::from(&self.string);
-                self.string.clear();
-                return AppEvents::ClosePopUp;
-            }
             _ => {}
         };
         AppEvents::None
@@ -0,0 +1,176 @@
+use crate::controller::{AppEvents, State};
+use crate::file_manager::FileManager;
+use crate::keymap::Action;
+use crate::message::{Message, MessageReceiver, MessageSender};
+use crate::trash::TrashEntry;
+use crossterm::event::KeyEvent;
+use ratatui::Frame;
+use ratatui::layout::Constraint;
+use ratatui::prelude::{Line, Style, Stylize};
+use ratatui::symbols::border;
+use ratatui::widgets::{Block, Row, Table, TableState};
+
+enum MessageSource {
+    None,
+    PurgeConfirmationPrompt,
+}
+
+///Window that lists the content of the trash and allows restoring or purging single items
+pub struct TrashTable {
+    table_state: TableState,
+    entries: Vec<TrashEntry>,
+    message_source: MessageSource,
+    message: Option<Message>,
+}
+
+impl TrashTable {
+    pub fn new() -> TrashTable {
+        let mut trash_table = TrashTable {
+            table_state: TableState::new(),
+            entries: Vec::new(),
+            message_source: MessageSource::None,
+            message: None,
+        };
+        trash_table.table_state.select_first();
+        trash_table
+    }
+
+    fn reload(&mut self, file_manager: &mut FileManager) {
+        self.entries = file_manager.trash_entries();
+        match self.table_state.selected() {
+            _ if self.entries.is_empty() => self.table_state.select(None),
+            Some(index) if index >= self.entries.len() => {
+                self.table_state.select(Some(self.entries.len() - 1))
+            }
+            None => self.table_state.select_first(),
+            _ => {}
+        }
+    }
+
+    fn selected_entry(&self) -> Option<&TrashEntry> {
+        self.entries.get(self.table_state.selected()?)
+    }
+
+    fn restore_selected(&mut self, file_manager: &mut FileManager) {
+        if let Some(entry) = self.selected_entry().cloned() {
+            file_manager.restore_from_trash(&entry);
+            self.reload(file_manager);
+        }
+    }
+}
+
+impl MessageReceiver for TrashTable {
+    fn handle_message(&mut self, message: Option<Message>, file_manager: &mut FileManager) {
+        match self.message_source {
+            MessageSource::PurgeConfirmationPrompt => {
+                if let Some(Message::Bool(true)) = message
+                    && let Some(entry) = self.selected_entry().cloned()
+                {
+                    file_manager.purge_from_trash(&entry);
+                    self.reload(file_manager);
+                }
+                self.message_source = MessageSource::None;
+            }
+            MessageSource::None => {}
+        }
+    }
+}
+
+impl MessageSender for TrashTable {
+    fn get_message(&mut self) -> Option<Message> {
+        self.message.take()
+    }
+}
+
+impl State for TrashTable {
+    fn enter(&mut self, file_manager: &mut FileManager) {
+        self.reload(file_manager);
+    }
+
+    fn handle_key_event(
+        &mut self,
+        key_event: KeyEvent,
+        file_manager: &mut FileManager,
+    ) -> AppEvents {
+        let keymap = file_manager.keymap();
+        match keymap.popup_action(&key_event) {
+            Some(Action::Close) => return AppEvents::ChangeToExplorerWindow,
+            _ if keymap.is_bound(&key_event, Action::OpenTrash) => {
+                return AppEvents::ChangeToExplorerWindow;
+            }
+            Some(Action::Down) => match self.table_state.selected() {
+                Some(selected) if selected + 1 >= self.entries.len() => {
+                    self.table_state.select_first()
+                }
+                _ => self.table_state.select_next(),
+            },
+            Some(Action::Up) => match self.table_state.selected() {
+                Some(0) | None => self.table_state.select_last(),
+                _ => self.table_state.select_previous(),
+            },
+            Some(Action::GoTop) => self.table_state.select_first(),
+            Some(Action::GoBottom) => self.table_state.select_last(),
+            Some(Action::Confirm) => self.restore_selected(file_manager),
+            _ if keymap.is_bound(&key_event, Action::Restore) => {
+                self.restore_selected(file_manager)
+            }
+            //delete selected entry permanently
+            _ if keymap.is_bound(&key_event, Action::Purge) => {
+                let Some(entry) = self.selected_entry() else {
+                    return AppEvents::None;
+                };
+                self.message = Some(Message::String(format!(
+                    "\"{}\" will be deleted permanently, are you sure?",
+                    entry.name()
+                )));
+                self.message_source = MessageSource::PurgeConfirmationPrompt;
+                return AppEvents::OpenConfirmationPopup;
+            }
+            _ => {}
+        }
+        AppEvents::None
+    }
+
+    fn draw(&mut self, frame: &mut Frame, file_manager: &mut FileManager) {
+        let title = Line::from("TRASH").bold();
+        let keymap = file_manager.keymap();
+        let help_text = Line::from(format!(
+            "restore:{} delete:{} back:{}",
+            keymap.hint(Action::Restore),
+            keymap.hint(Action::Purge),
+            keymap.hint(Action::Close)
+        ));
+        let table_block = Block::bordered()
+            .title(title.left_aligned())
+            .border_set(border::THICK)
+            .title_bottom(help_text.right_aligned().bold());
+
+        let header = Row::new(vec!["NAME", "ORIGINAL PATH", "DELETED"])
+            .bold()
+            .dark_gray();
+        let rows: Vec<Row> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let row = Row::new(vec![
+                    entry.name(),
+                    entry.original.display().to_string(),
+                    entry.deletion_date.replace('T', " "),
+                ]);
+                if entry.file.is_dir() { row.blue() } else { row }
+            })
+            .collect();
+        let widths = [
+            Constraint::Percentage(25),
+            Constraint::Percentage(55),
+            Constraint::Length(19),
+        ];
+
+        let table = Table::new(rows, widths)
+            .block(table_block)
+            .header(header)
+            .row_highlight_style(Style::new().green());
+
+        frame.render_stateful_widget(table, frame.area(), &mut self.table_state);
+    }
+}